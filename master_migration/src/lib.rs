@@ -9,10 +9,26 @@ impl MigratorTrait for MasterMigrator {
             Box::new(m20240101_000001_create_tenants_table::Migration),
             Box::new(m20240101_000002_create_users_table::Migration),
             Box::new(m20240101_000003_create_permissions_table::Migration),
+            Box::new(m20240101_000004_scope_user_email_to_tenant::Migration),
+            Box::new(m20240101_000005_create_api_clients_table::Migration),
+            Box::new(m20240101_000006_create_audit_log_table::Migration),
+            Box::new(m20240101_000007_add_region_to_tenants_table::Migration),
+            Box::new(m20240101_000008_create_idempotency_keys_table::Migration),
+            Box::new(m20240101_000009_add_lower_email_index_to_users_table::Migration),
+            Box::new(m20240101_000010_create_revoked_tokens_table::Migration),
+            Box::new(m20240101_000011_create_password_resets_table::Migration),
         ]
     }
 }
 
 pub mod m20240101_000001_create_tenants_table;
 pub mod m20240101_000002_create_users_table;
-pub mod m20240101_000003_create_permissions_table; 
\ No newline at end of file
+pub mod m20240101_000003_create_permissions_table;
+pub mod m20240101_000004_scope_user_email_to_tenant;
+pub mod m20240101_000005_create_api_clients_table;
+pub mod m20240101_000006_create_audit_log_table;
+pub mod m20240101_000007_add_region_to_tenants_table;
+pub mod m20240101_000008_create_idempotency_keys_table;
+pub mod m20240101_000009_add_lower_email_index_to_users_table;
+pub mod m20240101_000010_create_revoked_tokens_table;
+pub mod m20240101_000011_create_password_resets_table;
\ No newline at end of file
@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+/// Replaces the global unique constraint on `users.email` with one on `(tenant_id, email)`,
+/// so the same address can be registered in more than one tenant.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const TENANT_EMAIL_INDEX: &str = "idx_users_tenant_id_email";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key")
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(TENANT_EMAIL_INDEX)
+                    .table(Users::Table)
+                    .col(Users::TenantId)
+                    .col(Users::Email)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(TENANT_EMAIL_INDEX).table(Users::Table).to_owned())
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email)")
+            .await
+            .map(|_| ())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    TenantId,
+    Email,
+}
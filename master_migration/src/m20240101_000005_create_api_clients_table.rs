@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiClients::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(ApiClients::Id).string().not_null().primary_key())
+                    .col(ColumnDef::new(ApiClients::TenantId).string().not_null())
+                    .col(ColumnDef::new(ApiClients::Name).string().not_null())
+                    .col(ColumnDef::new(ApiClients::SecretHash).string().not_null())
+                    .col(ColumnDef::new(ApiClients::Scopes).json().not_null())
+                    .col(ColumnDef::new(ApiClients::Status).string().not_null().default("active"))
+                    .col(ColumnDef::new(ApiClients::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .col(ColumnDef::new(ApiClients::UpdatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_api_clients_tenant_id")
+                            .from(ApiClients::Table, ApiClients::TenantId)
+                            .to(Tenants::Table, Tenants::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiClients::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiClients {
+    Table,
+    Id,
+    TenantId,
+    Name,
+    SecretHash,
+    Scopes,
+    Status,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Tenants {
+    Table,
+    Id,
+}
@@ -0,0 +1,48 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Keys are unique per scope (a tenant id, or empty for tenant creation); the primary key
+        // is what lets only one of two concurrent requests with the same key proceed
+        manager
+            .create_table(
+                Table::create()
+                    .table(IdempotencyKeys::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(IdempotencyKeys::Scope).string().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::Key).string().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::RequestHash).string().not_null())
+                    .col(ColumnDef::new(IdempotencyKeys::StatusCode).small_integer().null())
+                    .col(ColumnDef::new(IdempotencyKeys::ResponseBody).json().null())
+                    .col(ColumnDef::new(IdempotencyKeys::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .primary_key(
+                        Index::create()
+                            .col(IdempotencyKeys::Scope)
+                            .col(IdempotencyKeys::Key),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(IdempotencyKeys::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum IdempotencyKeys {
+    Table,
+    Scope,
+    Key,
+    RequestHash,
+    StatusCode,
+    ResponseBody,
+    CreatedAt,
+}
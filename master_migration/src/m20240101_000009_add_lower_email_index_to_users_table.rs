@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+/// Makes emails unique per tenant regardless of case, and lets lookups on `LOWER(email)` use an
+/// index.
+///
+/// Fails if a tenant already has two accounts whose emails differ only in case; those have to
+/// be merged by hand before the migration can run.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const TENANT_LOWER_EMAIL_INDEX: &str = "idx_users_tenant_id_lower_email";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sea-query can't express expression indexes, so this one is written by hand
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                "CREATE UNIQUE INDEX IF NOT EXISTS {} ON users (tenant_id, LOWER(email))",
+                TENANT_LOWER_EMAIL_INDEX
+            ))
+            .await
+            .map(|_| ())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(&format!("DROP INDEX IF EXISTS {}", TENANT_LOWER_EMAIL_INDEX))
+            .await
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Rows are only needed until the token would have expired anyway, so expires_at is
+        // kept to purge them
+        manager
+            .create_table(
+                Table::create()
+                    .table(RevokedTokens::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(RevokedTokens::Jti).string().not_null().primary_key())
+                    .col(ColumnDef::new(RevokedTokens::TenantId).string().not_null())
+                    .col(ColumnDef::new(RevokedTokens::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(RevokedTokens::RevokedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_revoked_tokens_expires_at")
+                    .table(RevokedTokens::Table)
+                    .col(RevokedTokens::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RevokedTokens::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum RevokedTokens {
+    Table,
+    Jti,
+    TenantId,
+    ExpiresAt,
+    RevokedAt,
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Only a SHA-256 of each reset token is stored, so a leaked table can't be used to
+        // reset passwords
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordResets::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PasswordResets::TokenHash).string().not_null().primary_key())
+                    .col(ColumnDef::new(PasswordResets::UserId).string().not_null())
+                    .col(ColumnDef::new(PasswordResets::TenantId).string().not_null())
+                    .col(ColumnDef::new(PasswordResets::ExpiresAt).timestamp().not_null())
+                    .col(ColumnDef::new(PasswordResets::Used).boolean().not_null().default(false))
+                    .col(ColumnDef::new(PasswordResets::CreatedAt).timestamp().not_null().default(Expr::current_timestamp()))
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_password_resets_user_id")
+                            .from(PasswordResets::Table, PasswordResets::UserId)
+                            .to(Users::Table, Users::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_password_resets_user_id")
+                    .table(PasswordResets::Table)
+                    .col(PasswordResets::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordResets::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PasswordResets {
+    Table,
+    TokenHash,
+    UserId,
+    TenantId,
+    ExpiresAt,
+    Used,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Id,
+}
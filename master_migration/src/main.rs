@@ -1,4 +1,4 @@
-use sea_orm::{Database, ConnectOptions};
+use sea_orm::Database;
 use sea_orm_migration::MigratorTrait;
 use master_migration::MasterMigrator;
 use std::env;
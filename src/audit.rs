@@ -0,0 +1,15 @@
+//! Action names written to the master `audit_log` table.
+//!
+//! Names are `<target>.<verb>` so entries for one kind of target sort and filter together.
+
+pub const USER_REGISTERED: &str = "user.registered";
+pub const USER_CREATED: &str = "user.created";
+pub const USER_UPDATED: &str = "user.updated";
+pub const USER_DELETED: &str = "user.deleted";
+pub const USER_PASSWORD_CHANGED: &str = "user.password_changed";
+pub const USER_PASSWORD_RESET: &str = "user.password_reset";
+pub const USER_PERMISSIONS_CHANGED: &str = "user.permissions_changed";
+pub const API_CLIENT_CREATED: &str = "api_client.created";
+pub const TENANT_CREATED: &str = "tenant.created";
+pub const TENANT_STATUS_CHANGED: &str = "tenant.status_changed";
+pub const TENANT_DELETED: &str = "tenant.deleted";
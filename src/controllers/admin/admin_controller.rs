@@ -0,0 +1,388 @@
+use std::collections::BTreeMap;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde_json::json;
+use tracing::{error, info};
+use crate::{
+    audit,
+    extract::Json,
+    error::ApiError,
+    middlewares::{TokenInspection, inspect_jwt_token, require_any_permission, require_permission},
+    pagination::{Paginated, page_size, validate_page},
+    permissions,
+    multi_tenancy::{
+        ConnectionStats, MasterService, MigrationOutcome, MigrationStatus, SchemaDrift, audit_retention_cutoff,
+        ensure_tenant_deletable, parse_tenant_status, validate_tenant_id,
+    },
+    types::config::AppConfig,
+    types::shared::{
+        ApiClientCreatedResponse, AppState, AuditEntry, AuditLogEntry, AuditPurgeParams, AuditPurgeResponse, AuditUrlParams, DecodeTokenRequest, CreateApiClientRequest,
+        GrantPermissionRequest, GrantPermissionResponse, SetTenantStatusRequest, SetUserPermissionsRequest, TenantContext, TenantResponse,
+        UserPermissionsResponse,
+    },
+};
+
+/// Largest number of users a single permission grant may target.
+const MAX_GRANT_USERS: usize = 1_000;
+
+/// Administering the caller's own tenant requires `tenants:admin` (or `platform:admin`).
+pub async fn require_tenant_admin(tenant_context: &TenantContext) -> Result<(), ApiError> {
+    require_any_permission(tenant_context, &[permissions::TENANTS_ADMIN, permissions::PLATFORM_ADMIN])
+        .await
+        .map_err(|_| ApiError::Forbidden)
+}
+
+/// Endpoints that act across tenants, such as creating, migrating or deleting them, require
+/// `platform:admin`.
+pub async fn require_platform_admin(tenant_context: &TenantContext) -> Result<(), ApiError> {
+    require_permission(tenant_context, permissions::PLATFORM_ADMIN)
+        .await
+        .map_err(|_| ApiError::Forbidden)
+}
+
+/// Endpoints naming a tenant need `tenants:admin` for the caller's own tenant and
+/// `platform:admin` for any other.
+pub async fn require_admin_of(tenant_context: &TenantContext, tenant_id: &str) -> Result<(), ApiError> {
+    if tenant_id == tenant_context.tenant_id {
+        require_tenant_admin(tenant_context).await
+    } else {
+        require_platform_admin(tenant_context).await
+    }
+}
+
+/// Reports schema drift for a single tenant database.
+pub async fn tenant_schema_drift(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<SchemaDrift>>, ApiError> {
+    require_admin_of(&tenant_context, &tenant_id).await?;
+    validate_tenant_id(&tenant_id).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    let drift = state.tenant_manager.detect_drift(&tenant_id).await.map_err(|e| {
+        error!(tenant_id = %tenant_id, error = %e, "Failed to detect tenant schema drift");
+        ApiError::Internal
+    })?;
+    
+    Ok(Json(drift))
+}
+
+/// Reports which migrations a tenant database has applied and how many are pending.
+pub async fn tenant_migration_status(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<MigrationStatus>, ApiError> {
+    require_admin_of(&tenant_context, &tenant_id).await?;
+    validate_tenant_id(&tenant_id).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    let status = state.tenant_manager.tenant_migration_status(&tenant_id).await.map_err(|e| {
+        error!(tenant_id = %tenant_id, error = %e, "Failed to read tenant migration status");
+        ApiError::from_tenant_connection(e)
+    })?;
+    
+    Ok(Json(status))
+}
+
+/// Applies pending tenant migrations to every active tenant, reporting the outcome per tenant.
+/// Tenants that fail don't stop the others from being migrated.
+pub async fn migrate_all_tenants(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<Json<BTreeMap<String, MigrationOutcome>>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    
+    let outcomes = state.tenant_manager.migrate_all_tenants().await.map_err(|e| {
+        error!(error = %e, "Failed to run tenant migrations");
+        ApiError::Internal
+    })?;
+    
+    let failed = outcomes
+        .iter()
+        .filter(|(_, outcome)| matches!(outcome, MigrationOutcome::Failed { .. }))
+        .count();
+    info!(tenants = outcomes.len(), failed, "Tenant migration run finished");
+    
+    Ok(Json(outcomes.into_iter().collect()))
+}
+
+/// Runs drift detection across all active tenants and returns those that drifted.
+pub async fn schema_drift_sweep(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<Json<BTreeMap<String, Vec<SchemaDrift>>>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    
+    let report = state.tenant_manager.detect_drift_all().await.map_err(|e| {
+        error!(error = %e, "Failed to run schema drift sweep");
+        ApiError::Internal
+    })?;
+    
+    Ok(Json(report))
+}
+
+/// Grants one permission to many users of the caller's tenant at once, reporting the outcome
+/// per user. New permissions take effect in tokens issued after the grant.
+pub async fn grant_permission(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(request): Json<GrantPermissionRequest>,
+) -> Result<Json<GrantPermissionResponse>, ApiError> {
+    require_tenant_admin(&tenant_context).await?;
+    // Otherwise a tenant admin could make themselves a platform admin
+    if request.permission == permissions::PLATFORM_ADMIN {
+        require_platform_admin(&tenant_context).await?;
+    }
+    
+    if request.user_ids.is_empty() {
+        return Err(ApiError::BadRequest("user_ids must not be empty".to_string()));
+    }
+    if request.user_ids.len() > MAX_GRANT_USERS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} users can be granted a permission at once",
+            MAX_GRANT_USERS
+        )));
+    }
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await)
+        .with_max_permissions(state.max_token_permissions);
+    let results = master_service
+        .grant_permission(&tenant_context.tenant_id, &request.user_ids, &request.permission)
+        .await?;
+    
+    Ok(Json(GrantPermissionResponse {
+        permission: request.permission,
+        results,
+    }))
+}
+
+/// Registers a machine client for the caller's tenant. Scopes must be in the permissions
+/// catalog (`400` otherwise), and only a platform admin may hand out `platform:admin`.
+pub async fn create_api_client(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(request): Json<CreateApiClientRequest>,
+) -> Result<(StatusCode, Json<ApiClientCreatedResponse>), ApiError> {
+    require_tenant_admin(&tenant_context).await?;
+    if request.scopes.iter().any(|scope| scope == permissions::PLATFORM_ADMIN) {
+        require_platform_admin(&tenant_context).await?;
+    }
+    
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("name must not be empty".to_string()));
+    }
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await)
+        .with_max_permissions(state.max_token_permissions);
+    let (client_id, client_secret, scopes) = master_service
+        .create_api_client(&tenant_context.tenant_id, name, &request.scopes)
+        .await?;
+    
+    info!(tenant_id = %tenant_context.tenant_id, client_id = %client_id, "API client created");
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant_context.tenant_id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action: audit::API_CLIENT_CREATED,
+            target_id: client_id.clone(),
+            metadata: Some(json!({ "name": name, "scopes": scopes })),
+        })
+        .await;
+    
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiClientCreatedResponse {
+            client_id,
+            client_secret,
+            tenant_id: tenant_context.tenant_id,
+            name: name.to_string(),
+            scopes,
+        }),
+    ))
+}
+
+/// Replaces the permissions of one user in the caller's tenant. Every permission must be in the
+/// catalog. Like grants, the change applies to tokens issued afterwards.
+pub async fn set_user_permissions(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(user_id): Path<String>,
+    Json(request): Json<SetUserPermissionsRequest>,
+) -> Result<Json<UserPermissionsResponse>, ApiError> {
+    require_tenant_admin(&tenant_context).await?;
+    if request.permissions.iter().any(|permission| permission == permissions::PLATFORM_ADMIN) {
+        require_platform_admin(&tenant_context).await?;
+    }
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await)
+        .with_max_permissions(state.max_token_permissions);
+    let permissions = master_service
+        .set_user_permissions(&user_id, &tenant_context.tenant_id, &request.permissions)
+        .await?;
+    
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant_context.tenant_id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action: audit::USER_PERMISSIONS_CHANGED,
+            target_id: user_id.clone(),
+            metadata: Some(json!({ "permissions": permissions })),
+        })
+        .await;
+    
+    Ok(Json(UserPermissionsResponse { user_id, permissions }))
+}
+
+/// Sets a tenant's status to `active` or `suspended`. The tenant's cached connection pool is
+/// closed so the change applies to its very next request rather than after
+/// `TENANT_STATUS_RECHECK_SECS`. Suspending is also the first step of deleting a tenant.
+pub async fn set_tenant_status(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<SetTenantStatusRequest>,
+) -> Result<Json<TenantResponse>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    validate_tenant_id(&tenant_id).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let status = parse_tenant_status(&request.status)?;
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let tenant = master_service.set_tenant_status(&tenant_id, status).await?;
+    state.tenant_manager.invalidate_tenant(&tenant_id).await;
+    
+    info!(tenant_id = %tenant_id, status, "Tenant status changed");
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant_id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action: audit::TENANT_STATUS_CHANGED,
+            target_id: tenant_id,
+            metadata: Some(json!({ "status": status, "actor_tenant_id": tenant_context.tenant_id })),
+        })
+        .await;
+    
+    Ok(Json(tenant))
+}
+
+/// Deletes a suspended tenant: drops its database, then marks it `deleted`.
+///
+/// Returns `409 Conflict` unless the tenant is suspended. The database is dropped first so that
+/// a failed drop leaves the tenant suspended and the call can simply be retried.
+pub async fn delete_tenant(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(tenant_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    validate_tenant_id(&tenant_id).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let tenant = master_service
+        .get_tenant(&tenant_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound(format!("Tenant {} not found", tenant_id)))?;
+    ensure_tenant_deletable(&tenant.status)?;
+    
+    state.tenant_manager.drop_tenant_database(&tenant_id, tenant.region.as_deref()).await.map_err(|e| {
+        error!(tenant_id = %tenant_id, error = %e, "Failed to drop tenant database");
+        ApiError::Internal
+    })?;
+    master_service.delete_tenant(&tenant_id).await?;
+    
+    info!(tenant_id = %tenant_id, "Tenant deleted");
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant_id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action: audit::TENANT_DELETED,
+            target_id: tenant_id,
+            metadata: Some(json!({ "actor_tenant_id": tenant_context.tenant_id })),
+        })
+        .await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reports tenant connection cache occupancy and eviction rate.
+pub async fn connection_stats(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<Json<ConnectionStats>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    
+    Ok(Json(state.tenant_manager.stats().await))
+}
+
+/// Returns the configuration this instance loaded, with secrets replaced by `***`.
+pub async fn effective_config(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<Json<AppConfig>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    
+    Ok(Json(state.config.redacted()))
+}
+
+/// Lists audit log entries, newest first. Defaults to the caller's tenant; `tenant_id` selects
+/// another one, which requires `platform:admin`.
+pub async fn audit_index(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Query(params): Query<AuditUrlParams>,
+) -> Result<Json<Paginated<AuditLogEntry>>, ApiError> {
+    let tenant_id = params.tenant_id.as_deref().unwrap_or(&tenant_context.tenant_id);
+    require_admin_of(&tenant_context, tenant_id).await?;
+    validate_page(params.page, params.page_size)?;
+    
+    let page = params.page.unwrap_or(1);
+    let page_size = page_size(params.page_size, state.config.max_page_size);
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let entries = master_service
+        .list_audit(tenant_id, params.action.as_deref(), page, page_size)
+        .await?;
+    
+    Ok(Json(entries))
+}
+
+/// Purges a tenant's audit entries older than `AUDIT_RETENTION_DAYS` now, instead of waiting
+/// for the background purge. Defaults to the caller's tenant; `tenant_id` selects another one,
+/// which requires `platform:admin`.
+pub async fn audit_purge(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Query(params): Query<AuditPurgeParams>,
+) -> Result<Json<AuditPurgeResponse>, ApiError> {
+    let tenant_id = params.tenant_id.unwrap_or_else(|| tenant_context.tenant_id.clone());
+    require_admin_of(&tenant_context, &tenant_id).await?;
+    
+    let retention_days = state.config.audit_retention_days.ok_or_else(|| {
+        ApiError::BadRequest("No audit retention is configured (AUDIT_RETENTION_DAYS)".to_string())
+    })?;
+    let older_than = audit_retention_cutoff(chrono::Utc::now().naive_utc(), retention_days);
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let purged = master_service
+        .purge_audit_log(&tenant_id, older_than, state.config.audit_purge_batch_size)
+        .await?;
+    info!(tenant_id = %tenant_id, purged, "Purged expired audit entries on demand");
+    
+    Ok(Json(AuditPurgeResponse { tenant_id, older_than, purged }))
+}
+
+/// Validates a submitted JWT and returns its claims, or why it would be rejected. Key material
+/// is never included.
+pub async fn decode_token(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(request): Json<DecodeTokenRequest>,
+) -> Result<Json<TokenInspection>, ApiError> {
+    require_tenant_admin(&tenant_context).await?;
+    
+    let inspection = inspect_jwt_token(request.token.trim(), &state.jwt_keys);
+    info!(valid = inspection.valid, error = ?inspection.error, "Decoded token for inspection");
+    
+    Ok(Json(inspection))
+}
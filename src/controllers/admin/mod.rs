@@ -0,0 +1,3 @@
+pub mod admin_controller;
+
+pub use admin_controller::*;
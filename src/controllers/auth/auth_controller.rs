@@ -1,56 +1,491 @@
+use std::net::SocketAddr;
+use std::time::Duration;
 use axum::{
-    Json,
-    extract::State,
-    http::StatusCode,
+    extract::{ConnectInfo, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
 };
+use std::collections::HashSet;
+use tokio::time::{Instant, sleep_until};
+use serde_json::json;
+use uuid::Uuid;
+use tracing::{error, info, warn};
 use crate::{
-    types::shared::{AppState, LoginRequest, LoginResponse, CreateUserRequest, UserResponse, CreateTenantRequest, TenantResponse},
-    multi_tenancy::MasterService,
+    audit,
+    controllers::admin::require_platform_admin,
+    extract::Json,
+    error::{ApiError, is_unique_violation},
+    idempotency::{idempotent, request_fingerprint},
+    types::shared::{
+        AppState, LoginRequest, LoginResponse, CreateUserRequest, CreateTenantRequest, TenantResponse,
+        EmailAvailabilityParams, EmailAvailabilityResponse, EmailTenantsParams, EmailTenantsResponse,
+        ClientTokenRequest, ClientTokenResponse, AuditEntry, ChangePasswordRequest, TenantContext,
+        TenantsBulkResponse, TenantsBulkResult, PasswordResetRequest, PasswordResetRequestResponse,
+        PasswordResetConfirmRequest,
+    },
+    middlewares::create_jwt_token,
+    multi_tenancy::{MasterService, validate_tenant_id},
+    validation::{FieldErrors, validate_email, validate_password_strength},
 };
 
+/// Minimum time an email availability check takes, so response timing doesn't reveal
+/// whether the lookup found a match.
+const EMAIL_CHECK_MIN_DURATION: Duration = Duration::from_millis(250);
+
+/// Header carrying the tenant a login or registration request is addressed to.
+pub const TENANT_ID_HEADER: &str = "X-Tenant-ID";
+
+/// Reads and validates the tenant id from the `X-Tenant-ID` header.
+fn tenant_id_from_headers(headers: &HeaderMap) -> Result<String, ApiError> {
+    let tenant_id = headers
+        .get(TENANT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| ApiError::BadRequest(format!("{} header is required", TENANT_ID_HEADER)))?;
+    
+    validate_tenant_id(tenant_id).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(tenant_id.to_string())
+}
+
+/// Normalizes a requested tenant id (trimmed, lowercased) and validates it, generating a
+/// UUID-based id when none was supplied.
+fn resolve_new_tenant_id(requested: Option<&str>) -> Result<String, ApiError> {
+    let tenant_id = match requested.map(str::trim).filter(|id| !id.is_empty()) {
+        Some(id) => id.to_lowercase(),
+        None => Uuid::new_v4().simple().to_string(),
+    };
+    
+    validate_tenant_id(&tenant_id).map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    
+    Ok(tenant_id)
+}
+
+/// Ensures the tenant exists and is active, returning 404 otherwise.
+async fn require_active_tenant(master_service: &MasterService, tenant_id: &str) -> Result<(), ApiError> {
+    match master_service.get_tenant(tenant_id).await? {
+        Some(tenant) if tenant.status == "active" => Ok(()),
+        _ => Err(ApiError::NotFound(format!("Tenant '{}' not found", tenant_id))),
+    }
+}
+
 // Auth controller functions
 pub async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(login_data): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, StatusCode> {
-    // For demo purposes, we'll use a default tenant
-    let tenant_id = "demo_tenant";
+) -> Result<Json<LoginResponse>, ApiError> {
+    let tenant_id = tenant_id_from_headers(&headers)?;
     
     let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
-    let login_response = master_service.authenticate_user(login_data, tenant_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    require_active_tenant(&master_service, &tenant_id).await?;
+    
+    let login_response = master_service
+        .authenticate_user(login_data, &tenant_id, &state.tenant_manager, &state.jwt_keys, state.jwt_expiration)
+        .await?
+        .ok_or(ApiError::Unauthorized)?;
     
     Ok(Json(login_response))
 }
 
+/// Registers a user in the tenant named by `X-Tenant-ID`. Honours `Idempotency-Key`.
 pub async fn register(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(user_data): Json<CreateUserRequest>,
-) -> Result<Json<UserResponse>, StatusCode> {
-    // For demo purposes, we'll use a default tenant
-    let tenant_id = "demo_tenant";
+) -> Result<Response, ApiError> {
+    let tenant_id = tenant_id_from_headers(&headers)?;
+    validate_registration(&user_data, &state)?;
+    // Otherwise anyone could register themselves as a tenant admin
+    if user_data.permissions.is_some() {
+        return Err(ApiError::Forbidden);
+    }
     
-    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
-    let user = master_service.create_user(user_data, tenant_id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await)
+        .with_max_permissions(state.max_token_permissions);
+    require_active_tenant(&master_service, &tenant_id).await?;
     
-    Ok(Json(user))
+    let fingerprint = request_fingerprint("register", &user_data);
+    idempotent(&master_service, &headers, &tenant_id, fingerprint, async {
+        let user = master_service.create_user(user_data, &tenant_id, &state.password_policy).await?;
+        master_service
+            .try_record_audit(AuditEntry {
+                tenant_id: tenant_id.clone(),
+                actor_user_id: Some(user.id.clone()),
+                action: audit::USER_REGISTERED,
+                target_id: user.id.clone(),
+                metadata: None,
+            })
+            .await;
+        
+        Ok((StatusCode::OK, user))
+    })
+    .await
 }
 
+/// Checks every field of a registration request, reporting all invalid ones together.
+fn validate_registration(user_data: &CreateUserRequest, state: &AppState) -> Result<(), ApiError> {
+    let mut errors = FieldErrors::new();
+    
+    let email = errors.require("email", Some(&user_data.email), "Email is required");
+    if !email.is_empty()
+        && let Err(e) = validate_email(&email)
+    {
+        errors.add("email", e.to_string());
+    }
+    if user_data.password.is_empty() {
+        errors.add("password", "Password is required");
+    } else if let Err(e) = validate_password_strength(&user_data.password, &state.password_policy) {
+        errors.add("password", e.to_string());
+    }
+    errors.require("first_name", Some(&user_data.first_name), "First name is required");
+    errors.require("last_name", Some(&user_data.last_name), "Last name is required");
+    
+    errors.into_result()
+}
+
+/// Creates a tenant and its database. Honours `Idempotency-Key`. Requires `platform:admin`.
 pub async fn create_tenant(
     State(state): State<AppState>,
+    tenant_context: TenantContext,
+    headers: HeaderMap,
     Json(tenant_data): Json<CreateTenantRequest>,
-) -> Result<Json<TenantResponse>, StatusCode> {
+) -> Result<Response, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    
     let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
     
+    // Tenant creation isn't tied to a tenant, so its keys share one global scope
+    let fingerprint = request_fingerprint("create_tenant", &tenant_data);
+    idempotent(&master_service, &headers, "", fingerprint, async {
+        let tenant = provision_tenant(&state, &master_service, &tenant_context, tenant_data).await?;
+        Ok((StatusCode::OK, tenant))
+    })
+    .await
+}
+
+/// Largest number of tenants a single bulk creation may provision; each gets its own database.
+const MAX_BULK_TENANTS: usize = 50;
+
+/// Creates many tenants at once, reporting the outcome per tenant. Ids are checked up front,
+/// including for duplicates within the batch; an entry that fails, then or while its database
+/// is created, doesn't stop the others. Requires `platform:admin`.
+pub async fn create_tenants_bulk(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(requests): Json<Vec<CreateTenantRequest>>,
+) -> Result<Json<TenantsBulkResponse>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    
+    if requests.is_empty() {
+        return Err(ApiError::BadRequest("At least one tenant is required".to_string()));
+    }
+    if requests.len() > MAX_BULK_TENANTS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} tenants can be created at once",
+            MAX_BULK_TENANTS
+        )));
+    }
+    
+    // Resolve every id before provisioning anything, so duplicates are caught whatever their position
+    let mut seen_ids = HashSet::new();
+    let resolved: Vec<Result<String, String>> = requests
+        .iter()
+        .map(|tenant_data| match resolve_new_tenant_id(tenant_data.id.as_deref()) {
+            Ok(tenant_id) if !seen_ids.insert(tenant_id.clone()) => {
+                Err(format!("Duplicate tenant id {} in batch", tenant_id))
+            }
+            Ok(tenant_id) => Ok(tenant_id),
+            Err(e) => Err(e.to_string()),
+        })
+        .collect();
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    
+    let mut results = Vec::with_capacity(requests.len());
+    for (index, (mut tenant_data, tenant_id)) in requests.into_iter().zip(resolved).enumerate() {
+        let outcome = match tenant_id {
+            Ok(tenant_id) => {
+                // Pin generated ids, so the id checked for duplicates is the one created
+                tenant_data.id = Some(tenant_id);
+                provision_tenant(&state, &master_service, &tenant_context, tenant_data)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+            Err(error) => Err(error),
+        };
+        
+        results.push(match outcome {
+            Ok(tenant) => TenantsBulkResult { index, tenant: Some(tenant), error: None },
+            Err(error) => TenantsBulkResult { index, tenant: None, error: Some(error) },
+        });
+    }
+    
+    let failed = results.iter().filter(|result| result.error.is_some()).count();
+    info!(tenants = results.len(), failed, "Bulk tenant creation finished");
+    
+    Ok(Json(TenantsBulkResponse {
+        created: results.len() - failed,
+        failed,
+        results,
+    }))
+}
+
+/// Validates a tenant creation request, then creates the tenant row and its database. The
+/// creation is audited as an action of the calling admin.
+async fn provision_tenant(
+    state: &AppState,
+    master_service: &MasterService,
+    tenant_context: &TenantContext,
+    tenant_data: CreateTenantRequest,
+) -> Result<TenantResponse, ApiError> {
+    let mut errors = FieldErrors::new();
+    
+    // The id ends up in CREATE DATABASE, so it is checked before anything is created
+    let tenant_id = resolve_new_tenant_id(tenant_data.id.as_deref()).unwrap_or_else(|e| {
+        errors.add("id", e.to_string());
+        String::new()
+    });
+    errors.require("name", Some(&tenant_data.name), "Name is required");
+    if let Some(region) = &tenant_data.region
+        && !state.tenant_manager.serves_region(region)
+    {
+        errors.add("region", format!("Unknown region '{}'", region));
+    }
+    
+    errors.into_result()?;
+    
     // Create tenant in master database
-    let tenant = master_service.create_tenant(tenant_data).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let tenant = match master_service.create_tenant(tenant_id, tenant_data).await {
+        Ok(tenant) => tenant,
+        Err(e) if is_unique_violation(&e) => {
+            return Err(ApiError::Conflict("A tenant with this id already exists".to_string()));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    
+    // Create tenant database and run migrations. CREATE DATABASE can't run in a transaction,
+    // so on failure the tenant row is removed again instead of being rolled back.
+    if let Err(e) = state.tenant_manager.create_tenant_database(&tenant.id, tenant.region.as_deref()).await {
+        error!(tenant_id = %tenant.id, error = %e, "Failed to create tenant database");
+        
+        if let Err(cleanup_error) = master_service.discard_tenant(&tenant.id).await {
+            error!(tenant_id = %tenant.id, error = %cleanup_error, "Failed to remove tenant after database creation failed");
+        }
+        
+        return Err(ApiError::Internal);
+    }
+    
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant.id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action: audit::TENANT_CREATED,
+            target_id: tenant.id.clone(),
+            metadata: Some(json!({ "name": tenant.name })),
+        })
+        .await;
+    
+    Ok(tenant)
+} 
+
+/// Changes the caller's own password. The current password must be supplied; `401` if it is
+/// wrong, `400` if the new one fails the password policy.
+pub async fn change_password(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<StatusCode, ApiError> {
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    master_service
+        .change_password(
+            &tenant_context.user_id,
+            &tenant_context.tenant_id,
+            &request.current_password,
+            &request.new_password,
+            &state.password_policy,
+        )
+        .await?;
     
-    // Create tenant database and run migrations
-    state.tenant_manager.create_tenant_database(&tenant.id).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant_context.tenant_id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action: audit::USER_PASSWORD_CHANGED,
+            target_id: tenant_context.user_id.clone(),
+            metadata: None,
+        })
+        .await;
     
-    Ok(Json(tenant))
-} 
\ No newline at end of file
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Issues a password reset token for an account of the tenant named by `X-Tenant-ID`.
+///
+/// Always answers `200` with the same message, after the same minimum delay as the email
+/// availability check, so it can't be used to find out whether an account exists. The token
+/// is only included in the response when `PASSWORD_RESET_RETURN_TOKEN` is enabled.
+pub async fn request_password_reset(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<PasswordResetRequest>,
+) -> Result<Json<PasswordResetRequestResponse>, ApiError> {
+    let tenant_id = tenant_id_from_headers(&headers)?;
+    if !state.rate_limiter.check(&addr.ip().to_string()) {
+        warn!(client_ip = %addr.ip(), "Password reset request rate limited");
+        return Err(ApiError::TooManyRequests);
+    }
+    
+    let started = Instant::now();
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let ttl = chrono::Duration::seconds(state.config.password_reset_ttl_secs as i64);
+    let created = master_service
+        .create_password_reset(request.email.trim(), &tenant_id, ttl)
+        .await;
+    
+    sleep_until(started + EMAIL_CHECK_MIN_DURATION).await;
+    
+    let reset_token = created?;
+    if reset_token.is_some() {
+        info!(tenant_id = %tenant_id, "Password reset token issued");
+    }
+    
+    Ok(Json(PasswordResetRequestResponse {
+        message: "If the account exists, a password reset has been issued",
+        reset_token: reset_token.filter(|_| state.config.password_reset_return_token),
+    }))
+}
+
+/// Sets a new password with a reset token from `request_password_reset`. The token can be
+/// used once, and must not have expired.
+pub async fn confirm_password_reset(
+    State(state): State<AppState>,
+    Json(request): Json<PasswordResetConfirmRequest>,
+) -> Result<StatusCode, ApiError> {
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let (user_id, tenant_id) = master_service
+        .reset_password(request.token.trim(), &request.new_password, &state.password_policy)
+        .await?;
+    
+    master_service
+        .try_record_audit(AuditEntry {
+            tenant_id,
+            actor_user_id: Some(user_id.clone()),
+            action: audit::USER_PASSWORD_RESET,
+            target_id: user_id,
+            metadata: None,
+        })
+        .await;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revokes the caller's token, so it is rejected from now on rather than when it expires.
+/// Other instances pick up the revocation on their next refresh of the revoked token list.
+pub async fn logout(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<StatusCode, ApiError> {
+    if tenant_context.token_id.is_empty() {
+        return Err(ApiError::BadRequest(
+            "This token predates token revocation and can't be revoked".to_string(),
+        ));
+    }
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    master_service
+        .revoke_token(&tenant_context.token_id, &tenant_context.tenant_id, tenant_context.token_expires_at)
+        .await?;
+    state
+        .revoked_tokens
+        .insert(tenant_context.token_id, tenant_context.token_expires_at);
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reports whether an email can still be used to register in the given tenant.
+///
+/// Rate-limited per client IP to make enumeration impractical, and padded to a fixed minimum
+/// duration so existing and unknown emails are indistinguishable by timing.
+pub async fn email_available(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(params): Query<EmailAvailabilityParams>,
+) -> Result<Json<EmailAvailabilityResponse>, ApiError> {
+    if !state.rate_limiter.check(&addr.ip().to_string()) {
+        warn!(client_ip = %addr.ip(), "Email availability check rate limited");
+        return Err(ApiError::TooManyRequests);
+    }
+    
+    let started = Instant::now();
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let lookup = master_service.email_exists(params.email.trim(), &params.tenant).await;
+    
+    sleep_until(started + EMAIL_CHECK_MIN_DURATION).await;
+    
+    let exists = lookup?;
+    Ok(Json(EmailAvailabilityResponse { available: !exists }))
+}
+
+/// Lists the active tenants where an email has an account, for a tenant picker at login.
+///
+/// Shares the email availability check's rate limit and minimum duration, so it is no more
+/// useful for enumerating accounts than that endpoint.
+pub async fn email_tenants(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Query(params): Query<EmailTenantsParams>,
+) -> Result<Json<EmailTenantsResponse>, ApiError> {
+    if !state.rate_limiter.check(&addr.ip().to_string()) {
+        warn!(client_ip = %addr.ip(), "Email tenant lookup rate limited");
+        return Err(ApiError::TooManyRequests);
+    }
+    
+    let started = Instant::now();
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let lookup = master_service.tenants_for_email(params.email.trim()).await;
+    
+    sleep_until(started + EMAIL_CHECK_MIN_DURATION).await;
+    
+    Ok(Json(EmailTenantsResponse { tenants: lookup? }))
+}
+
+/// Client-credentials grant: exchanges an API client's id and secret for a JWT scoped to the
+/// client's tenant and permissions. Unlike `login`, no user profile is involved.
+pub async fn issue_client_token(
+    State(state): State<AppState>,
+    Json(credentials): Json<ClientTokenRequest>,
+) -> Result<Json<ClientTokenResponse>, ApiError> {
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let client = master_service
+        .authenticate_client(&credentials.client_id, &credentials.client_secret)
+        .await?
+        .ok_or_else(|| {
+            warn!(client_id = %credentials.client_id, "Rejected invalid client credentials");
+            ApiError::Unauthorized
+        })?;
+    
+    let access_token = create_jwt_token(
+        &client.client_id,
+        &client.tenant_id,
+        &client.scopes,
+        &state.jwt_keys,
+        state.jwt_expiration,
+    )
+    .map_err(|e| {
+        error!(client_id = %client.client_id, error = %e, "Failed to sign client token");
+        ApiError::Internal
+    })?;
+    
+    Ok(Json(ClientTokenResponse {
+        access_token,
+        token_type: "Bearer".to_string(),
+        expires_in: state.jwt_expiration,
+        scopes: client.scopes,
+    }))
+}
@@ -1,7 +1,13 @@
 pub mod auth;
 pub mod users;
 pub mod tenants;
+pub mod admin;
+pub mod orders;
+pub mod products;
 
 pub use auth::*;
 pub use users::*;
-pub use tenants::*; 
\ No newline at end of file
+pub use tenants::*;
+pub use admin::*;
+pub use orders::*;
+pub use products::*; 
\ No newline at end of file
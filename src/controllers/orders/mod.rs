@@ -0,0 +1,3 @@
+pub mod orders_controller;
+
+pub use orders_controller::*;
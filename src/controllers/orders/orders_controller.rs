@@ -0,0 +1,365 @@
+use axum::{extract::{Query, State}, http::StatusCode, response::IntoResponse};
+use uuid::Uuid;
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set, prelude::Decimal,
+};
+
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    extract::Json,
+    pagination::{page_size, truncate_to_cap, unpaginated_limit, validate_page},
+    entities::tenant::{
+        orders::{ActiveModel, Column, Entity, Model},
+        products, users,
+    },
+    error::{ApiError, is_foreign_key_violation},
+    types::orders::{OrderResponse, OrdersRequestBody, OrdersResponseType, OrdersUrlParams},
+    types::shared::{AppState, TenantContext},
+};
+
+/// Status assigned to new orders when the request doesn't specify one.
+const DEFAULT_ORDER_STATUS: &str = "pending";
+
+fn order_response(order: Model) -> OrderResponse {
+    OrderResponse {
+        id: order.id,
+        user_id: order.user_id,
+        product_id: order.product_id,
+        quantity: order.quantity,
+        total_amount: order.total_amount,
+        status: order.status,
+        created_at: order.created_at,
+        updated_at: order.updated_at,
+    }
+}
+
+fn validate_quantity(quantity: i32) -> Result<(), ApiError> {
+    if quantity <= 0 {
+        return Err(ApiError::UnprocessableEntity("Quantity must be greater than zero".to_string()));
+    }
+    Ok(())
+}
+
+fn order_total(price: Decimal, quantity: i32) -> Decimal {
+    price * Decimal::from(quantity)
+}
+
+async fn ensure_user_exists(tenant_db: &DatabaseConnection, user_id: &str) -> Result<(), ApiError> {
+    if users::Entity::find_by_id(user_id).one(tenant_db).await?.is_none() {
+        return Err(ApiError::UnprocessableEntity(format!("User {} does not exist", user_id)));
+    }
+    Ok(())
+}
+
+/// Loads the product an order refers to, so its price can be used for the total.
+async fn find_product(tenant_db: &DatabaseConnection, product_id: &str) -> Result<products::Model, ApiError> {
+    products::Entity::find_by_id(product_id)
+        .one(tenant_db)
+        .await?
+        .ok_or_else(|| ApiError::UnprocessableEntity(format!("Product {} does not exist", product_id)))
+}
+
+/// Fetches order information based on query parameters.
+///
+/// If an `id` is specified in the query, it returns a single order. Otherwise `page` and
+/// `page_size` decide between a paginated list and all matching orders.
+///
+/// # Arguments
+///
+/// * `params` - A `Query` extractor containing query parameters for order retrieval.
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code and
+///   serialized JSON data of the order(s).
+#[instrument(skip(state))]
+pub async fn orders_index(
+    Query(params): Query<OrdersUrlParams>,
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_page(params.page, params.page_size)?;
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    if let Some(id) = params.id {
+        let order = Entity::find_by_id(&id)
+            .one(&tenant_db)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Order with ID {} not found", id)))?;
+
+        return Ok((StatusCode::OK, Json(OrdersResponseType::SingleOrder(order_response(order)))));
+    }
+
+    let mut query = Entity::find();
+
+    // Apply filters
+    if let Some(user_id) = params.user_id {
+        query = query.filter(Column::UserId.eq(user_id));
+    }
+    if let Some(product_id) = params.product_id {
+        query = query.filter(Column::ProductId.eq(product_id));
+    }
+    if let Some(status) = params.status {
+        query = query.filter(Column::Status.eq(status));
+    }
+
+    // `id` breaks ties between orders created in the same instant, keeping pages stable
+    let query = query.order_by_desc(Column::CreatedAt).order_by_desc(Column::Id);
+
+    match params.page {
+        Some(page) => {
+            let page_size = page_size(params.page_size, state.config.max_page_size);
+            let paginator = query.paginate(&tenant_db, page_size as u64);
+
+            let total_count = paginator.num_items().await?;
+            let orders = paginator.fetch_page(page.saturating_sub(1) as u64).await.map_err(|e| {
+                error!(page = page, error = %e, "Database error while fetching paginated orders");
+                ApiError::Database(e)
+            })?;
+
+            Ok((
+                StatusCode::OK,
+                Json(OrdersResponseType::PaginatedOrders {
+                    orders: orders.into_iter().map(order_response).collect(),
+                    total_count,
+                    page,
+                    page_size,
+                }),
+            ))
+        }
+        None => {
+            let cap = state.config.max_unpaginated_rows;
+            let mut orders = query.limit(unpaginated_limit(cap)).all(&tenant_db).await.map_err(|e| {
+                error!(error = %e, "Database error while fetching all orders");
+                ApiError::Database(e)
+            })?;
+            if truncate_to_cap(&mut orders, cap) {
+                warn!(cap = cap, "Unpaginated order listing truncated at MAX_UNPAGINATED_ROWS; pass page instead");
+            }
+
+            Ok((
+                StatusCode::OK,
+                Json(OrdersResponseType::MultipleOrders(
+                    orders.into_iter().map(order_response).collect(),
+                )),
+            ))
+        }
+    }
+}
+
+/// Creates an order in the tenant database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `input` - An `OrdersRequestBody` with `user_id`, `product_id` and `quantity`, and
+///   optionally `status`.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `201 Created` and serialized JSON data of the created order, whose `total_amount` is the
+///   product price times the quantity. Returns `422 Unprocessable Entity` if the quantity is not
+///   positive or the referenced user or product does not exist.
+#[instrument(skip(state))]
+pub async fn orders_create(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(input): Json<OrdersRequestBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let user_id = input
+        .user_id
+        .ok_or_else(|| ApiError::BadRequest("User ID is required".to_string()))?;
+    let product_id = input
+        .product_id
+        .ok_or_else(|| ApiError::BadRequest("Product ID is required".to_string()))?;
+    let quantity = input
+        .quantity
+        .ok_or_else(|| ApiError::BadRequest("Quantity is required".to_string()))?;
+    validate_quantity(quantity)?;
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    // Each tenant's tables are separate, whether by database or by schema, so ids from another
+    // tenant are simply absent here
+    ensure_user_exists(&tenant_db, &user_id).await?;
+    let product = find_product(&tenant_db, &product_id).await?;
+
+    let order = ActiveModel {
+        id: Set(Uuid::new_v4().to_string()),
+        user_id: Set(user_id.clone()),
+        product_id: Set(product_id.clone()),
+        quantity: Set(quantity),
+        total_amount: Set(order_total(product.price, quantity)),
+        status: Set(input.status.unwrap_or_else(|| DEFAULT_ORDER_STATUS.to_string())),
+        ..Default::default()
+    };
+
+    match order.insert(&tenant_db).await {
+        Ok(created_order) => {
+            info!(order_id = %created_order.id, "Order created successfully");
+            Ok((StatusCode::CREATED, Json(order_response(created_order))))
+        }
+        // The referenced row was deleted between the existence check and the insert
+        Err(e) if is_foreign_key_violation(&e) => {
+            error!(user_id = %user_id, product_id = %product_id, "Order rejected: referenced row missing");
+            Err(ApiError::UnprocessableEntity(
+                "Referenced user or product does not exist".to_string(),
+            ))
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to create order in database");
+            Err(ApiError::Database(e))
+        }
+    }
+}
+
+/// Updates an order's product, quantity or status.
+///
+/// Changing the product or quantity recomputes `total_amount` from the product's current price.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `updates` - An `OrdersRequestBody` JSON object with the order `id` and the fields to change.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and serialized JSON data of the updated order.
+#[instrument(skip(state))]
+pub async fn orders_update(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(updates): Json<OrdersRequestBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let order_id = updates
+        .id
+        .ok_or_else(|| ApiError::BadRequest("Order ID is required".to_string()))?;
+    if let Some(quantity) = updates.quantity {
+        validate_quantity(quantity)?;
+    }
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let existing = Entity::find_by_id(&order_id)
+        .one(&tenant_db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Order with provided ID not found".to_string()))?;
+
+    if let Some(user_id) = &updates.user_id {
+        ensure_user_exists(&tenant_db, user_id).await?;
+    }
+
+    let reprice = updates.product_id.is_some() || updates.quantity.is_some();
+    let product_id = updates.product_id.clone().unwrap_or_else(|| existing.product_id.clone());
+    let quantity = updates.quantity.unwrap_or(existing.quantity);
+
+    let mut order: ActiveModel = existing.into();
+    if reprice {
+        let product = find_product(&tenant_db, &product_id).await?;
+        order.product_id = Set(product_id);
+        order.quantity = Set(quantity);
+        order.total_amount = Set(order_total(product.price, quantity));
+    }
+    if let Some(user_id) = updates.user_id {
+        order.user_id = Set(user_id);
+    }
+    if let Some(status) = updates.status {
+        order.status = Set(status);
+    }
+
+    match order.update(&tenant_db).await {
+        Ok(updated_order) => {
+            info!(order_id = %order_id, "Order updated successfully");
+            Ok((StatusCode::OK, Json(order_response(updated_order))))
+        }
+        Err(e) if is_foreign_key_violation(&e) => Err(ApiError::UnprocessableEntity(
+            "Referenced user or product does not exist".to_string(),
+        )),
+        Err(e) => {
+            error!(order_id = %order_id, error = %e, "Failed to update order in database");
+            Err(ApiError::Database(e))
+        }
+    }
+}
+
+/// Deletes an order from the tenant database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `input` - An `OrdersRequestBody` JSON object containing the order `id`.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and a message indicating that the order was deleted successfully.
+#[instrument(skip(state))]
+pub async fn orders_delete(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(input): Json<OrdersRequestBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let order_id = input
+        .id
+        .ok_or_else(|| ApiError::BadRequest("Order ID is required".to_string()))?;
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let result = Entity::delete_by_id(&order_id)
+        .exec(&tenant_db)
+        .await
+        .map_err(|e| {
+            error!(order_id = %order_id, error = %e, "Failed to delete order from database");
+            ApiError::Database(e)
+        })?;
+
+    if result.rows_affected == 0 {
+        return Err(ApiError::NotFound(format!("Order with ID {} not found", order_id)));
+    }
+
+    info!(order_id = %order_id, "Order deleted successfully");
+    Ok((StatusCode::OK, "Order deleted successfully".to_string()))
+}
@@ -0,0 +1,3 @@
+pub mod products_controller;
+
+pub use products_controller::*;
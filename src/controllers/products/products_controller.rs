@@ -0,0 +1,467 @@
+use axum::{extract::{Query, State}, http::StatusCode, response::IntoResponse};
+use uuid::Uuid;
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QuerySelect, Select, Set, prelude::Decimal, sea_query::Expr,
+};
+use tenant_migration::m20240101_000005_add_sku_to_products_table::SKU_INDEX;
+
+use tracing::{error, info, instrument, warn};
+
+use crate::{
+    extract::Json,
+    pagination::{page_size, truncate_to_cap, unpaginated_limit, validate_page},
+    entities::tenant::products::{ActiveModel, Column, Entity, Model},
+    error::{ApiError, is_unique_violation, unique_violation_constraint},
+    multi_tenancy::PRODUCT_NAME_INDEX,
+    types::config::ProductUniqueKey,
+    types::products::{
+        ProductResponse, ProductsCountUrlParams, ProductsRequestBody, ProductsResponseType,
+        ProductsUrlParams,
+    },
+    types::shared::{AppState, TenantContext},
+};
+
+fn product_response(product: Model) -> ProductResponse {
+    ProductResponse {
+        id: product.id,
+        name: product.name,
+        sku: product.sku,
+        description: product.description,
+        price: product.price,
+        created_at: product.created_at,
+        updated_at: product.updated_at,
+    }
+}
+
+/// Builds a product query filtered by name substring and an inclusive price range.
+fn filtered_query(
+    name: Option<String>,
+    min_price: Option<Decimal>,
+    max_price: Option<Decimal>,
+) -> Select<Entity> {
+    let mut query = Entity::find();
+
+    if let Some(name) = name {
+        query = query.filter(Column::Name.contains(name));
+    }
+    if let Some(min_price) = min_price {
+        query = query.filter(Column::Price.gte(min_price));
+    }
+    if let Some(max_price) = max_price {
+        query = query.filter(Column::Price.lte(max_price));
+    }
+
+    query
+}
+
+/// Applies the `sort_by` query parameter to a product listing. Products are listed newest
+/// first by default, with `created_at` and then `id` breaking ties so the order is stable.
+///
+/// `total_sold` sums order quantities per product with a correlated subquery, which is
+/// noticeably slower than the default ordering on tenants with many orders.
+fn sort_products(query: Select<Entity>, sort_by: Option<&str>) -> Result<Select<Entity>, ApiError> {
+    let query = match sort_by {
+        None => query,
+        Some("total_sold") => query.order_by_desc(Expr::cust(
+            "(SELECT COALESCE(SUM(quantity), 0) FROM orders WHERE orders.product_id = products.id)",
+        )),
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Cannot sort products by '{}'; allowed values are total_sold",
+                other
+            )));
+        }
+    };
+
+    Ok(query.order_by_desc(Column::CreatedAt).order_by_desc(Column::Id))
+}
+
+/// Rejects a create or update with `409 Conflict` if another product already has the SKU
+/// being written, or, when names are the tenant's unique key, the name being written. `name`
+/// and `sku` are `None` when the write doesn't set them.
+async fn ensure_unique_keys(
+    tenant_db: &DatabaseConnection,
+    unique_key: ProductUniqueKey,
+    name: Option<&str>,
+    sku: Option<&str>,
+    exclude_id: Option<&str>,
+) -> Result<(), ApiError> {
+    let mut checks = Vec::new();
+    if let Some(sku) = sku {
+        checks.push((ProductUniqueKey::Sku, Column::Sku.eq(sku)));
+    }
+    if unique_key == ProductUniqueKey::Name
+        && let Some(name) = name
+    {
+        checks.push((ProductUniqueKey::Name, Column::Name.eq(name)));
+    }
+
+    for (key, condition) in checks {
+        let mut query = Entity::find().filter(condition);
+        if let Some(id) = exclude_id {
+            query = query.filter(Column::Id.ne(id));
+        }
+        if query.one(tenant_db).await?.is_some() {
+            return Err(unique_key_conflict(key));
+        }
+    }
+    Ok(())
+}
+
+fn unique_key_conflict(unique_key: ProductUniqueKey) -> ApiError {
+    match unique_key {
+        ProductUniqueKey::Sku => ApiError::Conflict("A product with this SKU already exists".to_string()),
+        ProductUniqueKey::Name => ApiError::Conflict("A product with this name already exists".to_string()),
+    }
+}
+
+/// The `409 Conflict` for a write a unique index rejected, naming the key whose index fired.
+/// `None` if `e` is not a unique violation.
+fn unique_index_conflict(e: &DbErr) -> Option<ApiError> {
+    if !is_unique_violation(e) {
+        return None;
+    }
+    Some(match unique_violation_constraint(e) {
+        Some(SKU_INDEX) => unique_key_conflict(ProductUniqueKey::Sku),
+        Some(PRODUCT_NAME_INDEX) => unique_key_conflict(ProductUniqueKey::Name),
+        _ => ApiError::Conflict("A conflicting product already exists".to_string()),
+    })
+}
+
+fn validate_price(price: Decimal) -> Result<(), ApiError> {
+    if price.is_sign_negative() {
+        return Err(ApiError::BadRequest("Price must not be negative".to_string()));
+    }
+    Ok(())
+}
+
+/// Fetches product information based on query parameters.
+///
+/// If an `id` is specified in the query, it returns a single product. Otherwise `page` and
+/// `page_size` decide between a paginated list and all matching products.
+///
+/// # Arguments
+///
+/// * `params` - A `Query` extractor containing query parameters for product retrieval.
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code and
+///   serialized JSON data of the product(s).
+#[instrument(skip(state))]
+pub async fn products_index(
+    Query(params): Query<ProductsUrlParams>,
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<impl IntoResponse, ApiError> {
+    info!(
+        id = ?params.id,
+        page = ?params.page,
+        page_size = ?params.page_size,
+        tenant_id = %tenant_context.tenant_id,
+        "Fetching products"
+    );
+
+    validate_page(params.page, params.page_size)?;
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    if let Some(id) = params.id {
+        let product = Entity::find_by_id(&id)
+            .one(&tenant_db)
+            .await
+            .map_err(|e| {
+                error!(product_id = id, error = %e, "Database error while fetching product");
+                ApiError::Database(e)
+            })?
+            .ok_or_else(|| ApiError::NotFound(format!("Product with ID {} not found", id)))?;
+
+        return Ok((
+            StatusCode::OK,
+            Json(ProductsResponseType::SingleProduct(product_response(product))),
+        ));
+    }
+
+    let query = sort_products(
+        filtered_query(params.name, params.min_price, params.max_price),
+        params.sort_by.as_deref(),
+    )?;
+
+    match params.page {
+        Some(page) => {
+            let page_size = page_size(params.page_size, state.config.max_page_size);
+            let paginator = query.paginate(&tenant_db, page_size as u64);
+
+            let total_count = paginator.num_items().await?;
+            let products = paginator
+                .fetch_page(page.saturating_sub(1) as u64)
+                .await
+                .map_err(|e| {
+                    error!(page = page, error = %e, "Database error while fetching paginated products");
+                    ApiError::Database(e)
+                })?;
+
+            Ok((
+                StatusCode::OK,
+                Json(ProductsResponseType::PaginatedProducts {
+                    products: products.into_iter().map(product_response).collect(),
+                    total_count,
+                    page,
+                    page_size,
+                }),
+            ))
+        }
+        None => {
+            let cap = state.config.max_unpaginated_rows;
+            let mut products = query.limit(unpaginated_limit(cap)).all(&tenant_db).await.map_err(|e| {
+                error!(error = %e, "Database error while fetching all products");
+                ApiError::Database(e)
+            })?;
+            if truncate_to_cap(&mut products, cap) {
+                warn!(cap = cap, "Unpaginated product listing truncated at MAX_UNPAGINATED_ROWS; pass page instead");
+            }
+
+            Ok((
+                StatusCode::OK,
+                Json(ProductsResponseType::MultipleProducts(
+                    products.into_iter().map(product_response).collect(),
+                )),
+            ))
+        }
+    }
+}
+
+/// Creates a new product in the tenant database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `input` - A `ProductsRequestBody` JSON object with `name`, `price` and optionally
+///   `description`.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `201 Created` and serialized JSON data of the created product.
+#[instrument(skip(state))]
+pub async fn products_create(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(input): Json<ProductsRequestBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let name = input
+        .name
+        .ok_or_else(|| ApiError::BadRequest("Name is required".to_string()))?;
+    let price = input
+        .price
+        .ok_or_else(|| ApiError::BadRequest("Price is required".to_string()))?;
+    validate_price(price)?;
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    ensure_unique_keys(&tenant_db, state.product_unique_key, Some(&name), input.sku.as_deref(), None).await?;
+
+    let product = ActiveModel {
+        id: Set(Uuid::new_v4().to_string()),
+        name: Set(name),
+        sku: Set(input.sku),
+        description: Set(input.description),
+        price: Set(price),
+        ..Default::default()
+    };
+
+    // The unique indexes still catch a concurrent write that slipped past the check
+    let created_product = product.insert(&tenant_db).await.map_err(|e| {
+        if let Some(conflict) = unique_index_conflict(&e) {
+            return conflict;
+        }
+        error!(error = %e, "Failed to create product in database");
+        ApiError::Database(e)
+    })?;
+
+    info!(product_id = %created_product.id, "Product created successfully");
+    Ok((StatusCode::CREATED, Json(product_response(created_product))))
+}
+
+/// Updates a product by providing a JSON request body with the fields that should be updated.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `updates` - A `ProductsRequestBody` JSON object with the product `id` and the fields to change.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and serialized JSON data of the updated product.
+#[instrument(skip(state))]
+pub async fn products_update(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(updates): Json<ProductsRequestBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let product_id = updates
+        .id
+        .ok_or_else(|| ApiError::BadRequest("Product ID is required".to_string()))?;
+    if let Some(price) = updates.price {
+        validate_price(price)?;
+    }
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let existing = Entity::find_by_id(&product_id)
+        .one(&tenant_db)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Product with provided ID not found".to_string()))?;
+
+    ensure_unique_keys(
+        &tenant_db,
+        state.product_unique_key,
+        updates.name.as_deref(),
+        updates.sku.as_deref(),
+        Some(&product_id),
+    )
+    .await?;
+
+    let mut product: ActiveModel = existing.into();
+    if let Some(name) = updates.name {
+        product.name = Set(name);
+    }
+    if let Some(sku) = updates.sku {
+        product.sku = Set(Some(sku));
+    }
+    if let Some(description) = updates.description {
+        product.description = Set(Some(description));
+    }
+    if let Some(price) = updates.price {
+        product.price = Set(price);
+    }
+
+    let updated_product = product.update(&tenant_db).await.map_err(|e| {
+        if let Some(conflict) = unique_index_conflict(&e) {
+            return conflict;
+        }
+        error!(product_id = %product_id, error = %e, "Failed to update product in database");
+        ApiError::Database(e)
+    })?;
+
+    info!(product_id = %product_id, "Product updated successfully");
+    Ok((StatusCode::OK, Json(product_response(updated_product))))
+}
+
+/// Deletes a product from the tenant database.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `input` - A `ProductsRequestBody` JSON object containing the product `id`.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and a message indicating that the product was deleted successfully.
+#[instrument(skip(state))]
+pub async fn products_delete(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(input): Json<ProductsRequestBody>,
+) -> Result<impl IntoResponse, ApiError> {
+    let product_id = input
+        .id
+        .ok_or_else(|| ApiError::BadRequest("Product ID is required".to_string()))?;
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let result = Entity::delete_by_id(&product_id)
+        .exec(&tenant_db)
+        .await
+        .map_err(|e| {
+            error!(product_id = %product_id, error = %e, "Failed to delete product from database");
+            ApiError::Database(e)
+        })?;
+
+    if result.rows_affected == 0 {
+        return Err(ApiError::NotFound(format!("Product with ID {} not found", product_id)));
+    }
+
+    info!(product_id = %product_id, "Product deleted successfully");
+    Ok((StatusCode::OK, "Product deleted successfully".to_string()))
+}
+
+/// Returns the count of products in the tenant database, with optional filtering.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `params` - A `ProductsCountUrlParams` object containing filter parameters.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and a JSON response with the count of products.
+#[instrument(skip(state))]
+pub async fn products_count(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Query(params): Query<ProductsCountUrlParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let count = filtered_query(params.name, params.min_price, params.max_price)
+        .count(&tenant_db)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Database error while counting products");
+            ApiError::Database(e)
+        })?;
+
+    Ok((StatusCode::OK, Json(count)))
+}
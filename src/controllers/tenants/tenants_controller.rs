@@ -1,10 +1,19 @@
 use axum::{
-    Json,
-    extract::State,
-    http::StatusCode,
+    extract::{Query, State},
+    http::{Method, StatusCode, Uri, header},
+    response::IntoResponse,
 };
+use serde_json::{Value, json};
+use tracing::warn;
 use crate::{
-    types::shared::{AppState, TenantResponse},
+    extract::Json,
+    controllers::admin::require_platform_admin,
+    error::ApiError,
+    multi_tenancy::MasterService,
+    pagination::{page_size, validate_page},
+    types::shared::{
+        AppState, PaginatedTenantsResponse, ReadinessParams, TenantContext, TenantResponse, TenantsUrlParams,
+    },
 };
 
 // Tenants controller functions
@@ -12,9 +21,113 @@ pub async fn health_check() -> &'static str {
     "Multi-Tenant API is running!"
 }
 
+/// Fallback for paths no route matches, so they get the usual JSON error body.
+pub async fn route_not_found(uri: Uri) -> ApiError {
+    ApiError::NotFound(format!("No route for {}", uri.path()))
+}
+
+/// Fallback for known paths requested with a method they don't support.
+pub async fn method_not_allowed(method: Method, uri: Uri) -> ApiError {
+    ApiError::MethodNotAllowed(format!("Method {} is not allowed for {}", method, uri.path()))
+}
+
+/// Readiness probe: succeeds only when the master database answers the validation query.
+///
+/// With `deep=true` the canary tenant's database (`HEALTH_CANARY_TENANT`) must answer too,
+/// and the body reports each check separately. Without a canary configured, that check is
+/// reported as skipped.
+pub async fn readiness_check(
+    State(state): State<AppState>,
+    Query(params): Query<ReadinessParams>,
+) -> (StatusCode, Json<Value>) {
+    let master = state.tenant_manager.ping_master().await;
+    if let Err(e) = &master {
+        warn!(error = %e, "Readiness check failed: master database unreachable");
+    }
+    
+    if !params.deep {
+        return match master {
+            Ok(()) => (StatusCode::OK, Json(json!({ "status": "ready" }))),
+            Err(e) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({ "status": "unavailable", "reason": e.to_string() })),
+            ),
+        };
+    }
+    
+    let (tenant_ok, tenant_check) = match state.config.health_canary_tenant.as_deref() {
+        Some(tenant_id) => {
+            let result = state.tenant_manager.ping_tenant(tenant_id).await;
+            if let Err(e) = &result {
+                warn!(tenant_id = %tenant_id, error = %e, "Readiness check failed: canary tenant database unreachable");
+            }
+            let mut check = check_status(&result);
+            check["tenant_id"] = json!(tenant_id);
+            (result.is_ok(), check)
+        }
+        None => (true, json!({ "status": "skipped", "reason": "HEALTH_CANARY_TENANT is not set" })),
+    };
+    
+    let ready = master.is_ok() && tenant_ok;
+    let body = json!({
+        "status": if ready { "ready" } else { "unavailable" },
+        "checks": {
+            "master": check_status(&master),
+            "tenant": tenant_check,
+        },
+    });
+    
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(body))
+}
+
+fn check_status(result: &anyhow::Result<()>) -> Value {
+    match result {
+        Ok(()) => json!({ "status": "ok" }),
+        Err(e) => json!({ "status": "failed", "reason": e.to_string() }),
+    }
+}
+
+/// Prometheus scrape endpoint. Connection cache gauges are refreshed on each scrape.
+pub async fn metrics_export(State(state): State<AppState>) -> impl IntoResponse {
+    let stats = state.tenant_manager.stats().await;
+    metrics::gauge!("tenant_connections_cached").set(stats.cached_connections as f64);
+    metrics::gauge!("tenant_connections_capacity").set(stats.capacity as f64);
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
+
 pub async fn get_tenant_info(
     State(_state): State<AppState>,
 ) -> Result<Json<TenantResponse>, StatusCode> {
     // This would be implemented to get current tenant info
     todo!("Implement tenant info endpoint")
-} 
\ No newline at end of file
+} 
+
+/// Lists tenants page by page, optionally filtered by status. Requires `platform:admin`.
+pub async fn tenants_index(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Query(params): Query<TenantsUrlParams>,
+) -> Result<Json<PaginatedTenantsResponse>, ApiError> {
+    require_platform_admin(&tenant_context).await?;
+    validate_page(params.page, params.page_size)?;
+    
+    let page = params.page.unwrap_or(1);
+    let page_size = page_size(params.page_size, state.config.max_page_size);
+    
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let (tenants, total_count) = master_service
+        .list_tenants(page as u64, page_size as u64, params.status.as_deref())
+        .await?;
+    
+    Ok(Json(PaginatedTenantsResponse {
+        tenants,
+        total_count,
+        page,
+        page_size,
+    }))
+}
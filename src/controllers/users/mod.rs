@@ -1,3 +1,5 @@
 pub mod users_controller;
+pub mod user_filters;
 
-pub use users_controller::*; 
\ No newline at end of file
+pub use users_controller::*;
+pub use user_filters::*;
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ColumnTrait, Condition, QueryFilter, Select,
+    sea_query::{Expr, Func},
+};
+
+use crate::{
+    entities::tenant::users::{Column, Entity},
+    error::ApiError,
+};
+
+/// Comparison applied by a user listing filter, selected with a `__<op>` suffix on the field
+/// name. A bare field name means `contains`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterOp {
+    Eq,
+    StartsWith,
+    Contains,
+}
+
+/// One parsed filter query parameter, e.g. `email__eq=jane@example.com`.
+#[derive(Clone, Debug)]
+pub struct UserFilter {
+    pub column: Column,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+fn filterable_column(field: &str) -> Option<Column> {
+    match field {
+        "email" => Some(Column::Email),
+        "first_name" => Some(Column::FirstName),
+        "last_name" => Some(Column::LastName),
+        _ => None,
+    }
+}
+
+fn filter_op(op: &str) -> Option<FilterOp> {
+    match op {
+        "eq" => Some(FilterOp::Eq),
+        "startswith" => Some(FilterOp::StartsWith),
+        "contains" => Some(FilterOp::Contains),
+        _ => None,
+    }
+}
+
+/// Parses `field` / `field__op` query parameters into filters.
+///
+/// Fields are `email`, `first_name` and `last_name`; operators are `eq`, `startswith` and
+/// `contains`. Email filters ignore case. Anything else is rejected with `400` rather than silently ignored.
+pub fn parse_user_filters(params: &BTreeMap<String, String>) -> Result<Vec<UserFilter>, ApiError> {
+    params
+        .iter()
+        .map(|(key, value)| {
+            let (field, op) = match key.split_once("__") {
+                Some((field, op)) => (field, filter_op(op)),
+                None => (key.as_str(), Some(FilterOp::Contains)),
+            };
+
+            match (filterable_column(field), op) {
+                (Some(column), Some(op)) => Ok(UserFilter { column, op, value: value.clone() }),
+                _ => Err(ApiError::BadRequest(format!(
+                    "Unsupported filter '{}'; use email, first_name or last_name with an optional __eq, __startswith or __contains suffix",
+                    key
+                ))),
+            }
+        })
+        .collect()
+}
+
+pub fn apply_user_filters(mut query: Select<Entity>, filters: &[UserFilter]) -> Select<Entity> {
+    for filter in filters {
+        let value = filter.value.as_str();
+        query = query.filter(if matches!(filter.column, Column::Email) {
+            // New emails are stored lowercased, but older rows may not be
+            let email = Expr::expr(Func::lower(Expr::col(Column::Email)));
+            let value = value.to_lowercase();
+            match filter.op {
+                FilterOp::Eq => email.eq(value),
+                FilterOp::StartsWith => email.like(format!("{}%", value)),
+                FilterOp::Contains => email.like(format!("%{}%", value)),
+            }
+        } else {
+            match filter.op {
+                FilterOp::Eq => filter.column.eq(value),
+                FilterOp::StartsWith => filter.column.starts_with(value),
+                FilterOp::Contains => filter.column.contains(value),
+            }
+        });
+    }
+
+    query
+}
+
+/// Narrows `query` to users whose email, first name or last name contains `q`, ignoring case.
+/// A missing or blank `q` leaves the query unchanged; it is ANDed with any other filters.
+pub fn apply_user_search(query: Select<Entity>, q: Option<&str>) -> Select<Entity> {
+    let Some(q) = q.map(str::trim).filter(|q| !q.is_empty()) else {
+        return query;
+    };
+
+    let pattern = format!("%{}%", q.to_lowercase());
+    let matches = [Column::Email, Column::FirstName, Column::LastName]
+        .into_iter()
+        .fold(Condition::any(), |condition, column| {
+            condition.add(Expr::expr(Func::lower(Expr::col(column))).like(pattern.clone()))
+        });
+
+    query.filter(matches)
+}
+
+/// Narrows `query` to active or deactivated users; `None` leaves it unchanged.
+pub fn apply_active_filter(query: Select<Entity>, is_active: Option<bool>) -> Select<Entity> {
+    match is_active {
+        Some(is_active) => query.filter(Column::IsActive.eq(is_active)),
+        None => query,
+    }
+}
+
+/// Rejects a creation window that ends before it starts.
+pub fn validate_created_range(
+    created_after: Option<NaiveDateTime>,
+    created_before: Option<NaiveDateTime>,
+) -> Result<(), ApiError> {
+    match (created_after, created_before) {
+        (Some(after), Some(before)) if after > before => Err(ApiError::BadRequest(
+            "created_after must not be later than created_before".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Narrows `query` to users created within the window; both ends are inclusive and optional.
+pub fn apply_created_range(
+    mut query: Select<Entity>,
+    created_after: Option<NaiveDateTime>,
+    created_before: Option<NaiveDateTime>,
+) -> Select<Entity> {
+    if let Some(after) = created_after {
+        query = query.filter(Column::CreatedAt.gte(after));
+    }
+    if let Some(before) = created_before {
+        query = query.filter(Column::CreatedAt.lte(before));
+    }
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn bare_field_means_contains() {
+        let filters = parse_user_filters(&params(&[("last_name", "Do")])).unwrap();
+
+        assert_eq!(filters.len(), 1);
+        assert!(matches!(filters[0].column, Column::LastName));
+        assert_eq!(filters[0].op, FilterOp::Contains);
+        assert_eq!(filters[0].value, "Do");
+    }
+
+    #[test]
+    fn operator_suffixes_are_parsed() {
+        let filters = parse_user_filters(&params(&[
+            ("email__eq", "jane@example.com"),
+            ("first_name__startswith", "Ja"),
+        ]))
+        .unwrap();
+
+        assert!(matches!(filters[0].column, Column::Email));
+        assert_eq!(filters[0].op, FilterOp::Eq);
+        assert!(matches!(filters[1].column, Column::FirstName));
+        assert_eq!(filters[1].op, FilterOp::StartsWith);
+    }
+
+    #[test]
+    fn unknown_fields_and_operators_are_rejected() {
+        for key in ["password", "id__eq", "email__regex", "email__"] {
+            let result = parse_user_filters(&params(&[(key, "x")]));
+            assert!(matches!(result, Err(ApiError::BadRequest(_))), "accepted {:?}", key);
+        }
+    }
+}
@@ -1,23 +1,147 @@
-use axum::{Extension, Json, extract::Query, http::StatusCode, response::IntoResponse};
+use std::collections::{BTreeMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
 use uuid::Uuid;
 
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter,
-    QueryOrder, Set,
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Select, Set, TransactionTrait,
+    sea_query::{Alias, Expr},
 };
 
-use tracing::{error, info, instrument};
+use tracing::{error, info, instrument, warn};
+
+use serde_json::json;
 
+use super::{
+    apply_active_filter, apply_created_range, apply_user_filters, apply_user_search, parse_user_filters,
+    validate_created_range,
+};
 use crate::{
+    audit,
+    extract::Json,
+    idempotency::{idempotent, request_fingerprint},
+    multi_tenancy::MasterService,
+    pagination::{page_size, paginate, truncate_to_cap, unpaginated_limit, validate_page},
+    types::users::display_name,
+    middlewares::require_any_permission,
+    permissions,
     entities::tenant::users::{Entity, Column, ActiveModel},
-    types::shared::{AppState, TenantContext},
+    error::{ApiError, is_unique_violation},
+    validation::{FieldErrors, validate_email},
+    types::shared::{AppState, AuditEntry, TenantContext},
     types::users::{
-        UserResponse, UsersCountUrlParams, UsersRequestBody, UsersResponseType, UsersUrlParams,
+        DistinctValueCount, UserResponse, UsersBulkResponse, UsersBulkResult, UsersCountUrlParams,
+        UsersDistinctResponse, UsersDistinctUrlParams, UsersGroupCountUrlParams, UsersRequestBody,
+        UsersResponseType, UsersSetActiveRequest, UsersUrlParams,
     },
 };
 
 // Password handling is done in master database, not tenant databases
 
+/// Largest number of users accepted by a single bulk create request.
+const MAX_BULK_USERS: usize = 500;
+
+/// Reading user profiles needs `users:read`; tenant admins may read them too.
+async fn require_users_read(tenant_context: &TenantContext) -> Result<(), ApiError> {
+    require_any_permission(tenant_context, &[permissions::USERS_READ, permissions::TENANTS_ADMIN])
+        .await
+        .map_err(|_| ApiError::Forbidden)
+}
+
+/// Changing user profiles needs `users:write`; tenant admins may change them too.
+async fn require_users_write(tenant_context: &TenantContext) -> Result<(), ApiError> {
+    require_any_permission(tenant_context, &[permissions::USERS_WRITE, permissions::TENANTS_ADMIN])
+        .await
+        .map_err(|_| ApiError::Forbidden)
+}
+
+/// Rejects a request that names a tenant other than the one in the caller's token. The
+/// `tenant_id` field is otherwise ignored; this stops it being mistaken for a way across tenants.
+fn ensure_own_tenant(tenant_context: &TenantContext, tenant_id: Option<&str>) -> Result<(), ApiError> {
+    match tenant_id {
+        Some(tenant_id) if tenant_id != tenant_context.tenant_id => {
+            warn!(
+                tenant_id = %tenant_context.tenant_id,
+                requested_tenant_id = %tenant_id,
+                "Rejected request for another tenant"
+            );
+            Err(ApiError::Forbidden)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Records a change to a user in the audit log, attributed to the caller.
+async fn audit_user_change(
+    state: &AppState,
+    tenant_context: &TenantContext,
+    action: &'static str,
+    user_id: &str,
+    metadata: Option<serde_json::Value>,
+) {
+    MasterService::new(state.tenant_manager.get_master_connection().await)
+        .try_record_audit(AuditEntry {
+            tenant_id: tenant_context.tenant_id.clone(),
+            actor_user_id: Some(tenant_context.user_id.clone()),
+            action,
+            target_id: user_id.to_string(),
+            metadata,
+        })
+        .await;
+}
+
+/// Maps a `sort_dir` query value to an ordering; descending when unspecified.
+fn sort_order(sort_dir: Option<&str>) -> Result<Order, ApiError> {
+    match sort_dir {
+        None | Some("desc") => Ok(Order::Desc),
+        Some("asc") => Ok(Order::Asc),
+        Some(other) => Err(ApiError::BadRequest(format!(
+            "Invalid sort_dir '{}'; allowed values are asc, desc",
+            other
+        ))),
+    }
+}
+
+/// Applies the `sort_by` and `sort_dir` query parameters to a user listing. Without them users
+/// are ordered by id, descending. Ties on any other sort field are broken by `created_at` and
+/// then `id`, in the same direction, so the order is stable across calls and pages.
+///
+/// `order_count` runs a correlated count against `orders` for every matching user, so it is
+/// noticeably slower than the column sorts on tenants with many users or orders.
+fn sort_users(
+    query: Select<Entity>,
+    sort_by: Option<&str>,
+    sort_dir: Option<&str>,
+) -> Result<Select<Entity>, ApiError> {
+    let order = sort_order(sort_dir)?;
+    let query = match sort_by {
+        None | Some("id") => return Ok(query.order_by(Column::Id, order)),
+        Some("created_at") => return Ok(query.order_by(Column::CreatedAt, order.clone()).order_by(Column::Id, order)),
+        Some("email") => query.order_by(Column::Email, order.clone()),
+        Some("first_name") => query.order_by(Column::FirstName, order.clone()),
+        Some("last_name") => query.order_by(Column::LastName, order.clone()),
+        Some("order_count") => query.order_by(
+            Expr::cust("(SELECT COUNT(*) FROM orders WHERE orders.user_id = users.id)"),
+            order.clone(),
+        ),
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Cannot sort users by '{}'; allowed values are id, email, first_name, last_name, created_at, order_count",
+                other
+            )));
+        }
+    };
+
+    Ok(query
+        .order_by(Column::CreatedAt, order.clone())
+        .order_by(Column::Id, order))
+}
+
 /// Fetches user information based on query parameters.
 ///
 /// This function queries the tenant database for user information using the provided query parameters.
@@ -39,9 +163,12 @@ use crate::{
 #[instrument(skip(state))]
 pub async fn users_index(
     Query(params): Query<UsersUrlParams>,
-    Extension(state): Extension<AppState>,
-    Extension(tenant_context): Extension<TenantContext>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_read(&tenant_context).await?;
+    ensure_own_tenant(&tenant_context, params.tenant_id.as_deref())?;
+
     info!(
         id = ?params.id,
         page = ?params.page,
@@ -50,6 +177,12 @@ pub async fn users_index(
         "Fetching users"
     );
 
+    // Validate sorting and filters up front so a bad value is rejected whichever branch runs
+    let filters = parse_user_filters(&params.filters)?;
+    validate_created_range(params.created_after, params.created_before)?;
+    let sorted = sort_users(Entity::find(), params.sort_by.as_deref(), params.sort_dir.as_deref())?;
+    validate_page(params.page, params.page_size)?;
+
     // Get tenant database connection
     let tenant_db = state
         .tenant_manager
@@ -57,10 +190,7 @@ pub async fn users_index(
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to get tenant database connection");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database connection error".to_string(),
-            )
+            ApiError::from_tenant_connection(e)
         })?;
 
     // Check if id is present.
@@ -84,9 +214,12 @@ pub async fn users_index(
                     let user_response = UserResponse {
                         id: user.id,
                         email: user.email,
+                        display_name: display_name(&user.first_name, &user.last_name),
                         first_name: user.first_name,
                         last_name: user.last_name,
                         tenant_id: tenant_context.tenant_id.clone(),
+                        version: user.version,
+                        is_active: user.is_active,
                         created_at: user.created_at,
                         updated_at: user.updated_at,
                     };
@@ -98,17 +231,11 @@ pub async fn users_index(
                 }
                 Ok(None) => {
                     error!(user_id = id, "User not found");
-                    Err((
-                        StatusCode::NOT_FOUND,
-                        format!("User with ID {} not found", id),
-                    ))
+                    Err(ApiError::NotFound(format!("User with ID {} not found", id)))
                 }
                 Err(e) => {
                     error!(user_id = id, error = %e, "Database error while fetching user");
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "Database error".to_string(),
-                    ))
+                    Err(ApiError::Database(e))
                 }
             }
         }
@@ -122,67 +249,50 @@ pub async fn users_index(
                 Some(page) => {
                     info!(page = page, page_size = ?params.page_size, "Fetching paginated users");
 
-                    let mut query = Entity::find();
+                    let query = apply_user_search(apply_user_filters(sorted, &filters), params.q.as_deref());
+                    let query = apply_created_range(query, params.created_after, params.created_before);
+                    let query = apply_active_filter(query, params.is_active);
 
-                    // Apply filters
-                    if let Some(email) = params.email {
-                        query = query.filter(Column::Email.contains(email));
-                    }
-                    if let Some(first_name) = params.first_name {
-                        query = query.filter(Column::FirstName.contains(first_name));
-                    }
-                    if let Some(last_name) = params.last_name {
-                        query = query.filter(Column::LastName.contains(last_name));
-                    }
-
-                    let paginator = query
-                        .order_by_desc(Column::Id)
-                        .paginate(&tenant_db, params.page_size.unwrap_or(25) as u64);
-                    
-                    let total_count = paginator.num_items().await.unwrap_or(0);
-                    let users = paginator
-                        .fetch_page((page - 1) as u64)
-                        .await;
-
-                    match users {
-                        Ok(users_result) => {
+                    let page_size = page_size(params.page_size, state.config.max_page_size);
 
-                            let user_responses: Vec<UserResponse> = users_result
-                                .into_iter()
-                                .map(|user| UserResponse {
-                                    id: user.id,
-                                    email: user.email,
-                                    first_name: user.first_name,
-                                    last_name: user.last_name,
-                                    tenant_id: tenant_context.tenant_id.clone(),
-                                    created_at: user.created_at,
-                                    updated_at: user.updated_at,
-                                })
-                                .collect();
+                    match paginate(query, &tenant_db, page, page_size).await {
+                        Ok(users_page) => {
+                            let users_page = users_page.map(|user| UserResponse {
+                                id: user.id,
+                                email: user.email,
+                                display_name: display_name(&user.first_name, &user.last_name),
+                                first_name: user.first_name,
+                                last_name: user.last_name,
+                                tenant_id: tenant_context.tenant_id.clone(),
+                                version: user.version,
+                                is_active: user.is_active,
+                                created_at: user.created_at,
+                                updated_at: user.updated_at,
+                            });
 
                             info!(
                                 page = page,
-                                user_count = user_responses.len(),
-                                total_count = total_count,
+                                user_count = users_page.items.len(),
+                                total_count = users_page.total_count,
                                 "Successfully fetched paginated users"
                             );
 
                             Ok((
                                 StatusCode::OK,
                                 Json(UsersResponseType::PaginatedUsers {
-                                    users: user_responses,
-                                    total_count,
-                                    page,
-                                    page_size: params.page_size.unwrap_or(25),
+                                    users: users_page.items,
+                                    total_count: users_page.total_count,
+                                    total_pages: users_page.total_pages,
+                                    page: users_page.page,
+                                    page_size: users_page.page_size,
+                                    has_next: users_page.has_next,
+                                    has_prev: users_page.has_prev,
                                 }),
                             ))
                         }
                         Err(e) => {
                             error!(page = page, error = %e, "Database error while fetching paginated users");
-                            Err((
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                "Database error".to_string(),
-                            ))
+                            Err(ApiError::Database(e))
                         }
                     }
                 }
@@ -190,34 +300,33 @@ pub async fn users_index(
                 None => {
                     info!("Fetching all users");
 
-                    let mut query = Entity::find();
-
-                    // Apply filters
-                    if let Some(email) = params.email {
-                        query = query.filter(Column::Email.contains(email));
-                    }
-                    if let Some(first_name) = params.first_name {
-                        query = query.filter(Column::FirstName.contains(first_name));
-                    }
-                    if let Some(last_name) = params.last_name {
-                        query = query.filter(Column::LastName.contains(last_name));
-                    }
+                    let query = apply_user_search(apply_user_filters(sorted, &filters), params.q.as_deref());
+                    let query = apply_created_range(query, params.created_after, params.created_before);
+                    let query = apply_active_filter(query, params.is_active);
 
+                    let cap = state.config.max_unpaginated_rows;
                     let users = query
-                        .order_by_desc(Column::Id)
+                        .limit(unpaginated_limit(cap))
                         .all(&tenant_db)
                         .await;
 
                     match users {
-                        Ok(users_result) => {
+                        Ok(mut users_result) => {
+                            if truncate_to_cap(&mut users_result, cap) {
+                                warn!(cap = cap, "Unpaginated user listing truncated at MAX_UNPAGINATED_ROWS; pass page instead");
+                            }
+
                             let user_responses: Vec<UserResponse> = users_result
                                 .into_iter()
                                 .map(|user| UserResponse {
                                     id: user.id,
                                     email: user.email,
+                                    display_name: display_name(&user.first_name, &user.last_name),
                                     first_name: user.first_name,
                                     last_name: user.last_name,
                                     tenant_id: tenant_context.tenant_id.clone(),
+                                    version: user.version,
+                                    is_active: user.is_active,
                                     created_at: user.created_at,
                                     updated_at: user.updated_at,
                                 })
@@ -234,10 +343,7 @@ pub async fn users_index(
                         }
                         Err(e) => {
                             error!(error = %e, "Database error while fetching all users");
-                            Err((
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                "Database error".to_string(),
-                            ))
+                            Err(ApiError::Database(e))
                         }
                     }
                 }
@@ -258,38 +364,47 @@ pub async fn users_index(
 ///
 /// # Returns
 ///
-/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
-///   `201 Created` and serialized JSON data of the created user.
-#[instrument(skip(state))]
+/// * `Result<Response>` - If successful, returns an HTTP response with a status code of
+///   `201 Created` and serialized JSON data of the created user. A request repeating an earlier
+///   `Idempotency-Key` gets the earlier response instead.
+#[instrument(skip(state, headers))]
 pub async fn users_create(
-    Extension(state): Extension<AppState>,
-    Extension(tenant_context): Extension<TenantContext>,
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    headers: HeaderMap,
     Json(input): Json<UsersRequestBody>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<Response, ApiError> {
+    require_users_write(&tenant_context).await?;
+    ensure_own_tenant(&tenant_context, input.tenant_id.as_deref())?;
+
+    let master_service = MasterService::new(state.tenant_manager.get_master_connection().await);
+    let fingerprint = request_fingerprint("users_create", &input);
+    idempotent(
+        &master_service,
+        &headers,
+        &tenant_context.tenant_id,
+        fingerprint,
+        create_user_profile(&state, &tenant_context, input),
+    )
+    .await
+}
+
+/// Validates `input` and inserts the user profile for `users_create`.
+async fn create_user_profile(
+    state: &AppState,
+    tenant_context: &TenantContext,
+    input: UsersRequestBody,
+) -> Result<(StatusCode, UserResponse), ApiError> {
     info!("Creating new user");
 
-    // Validate required fields
-    let email = input.email.ok_or_else(|| {
-        error!("Missing email in user creation request");
-        (StatusCode::BAD_REQUEST, "Email is required".to_string())
+    // Validate required fields, reporting all missing or invalid ones together
+    let (email, first_name, last_name) = validate_new_user(&input).inspect_err(|e| {
+        error!(error = %e, "Invalid user creation request");
     })?;
 
     // Note: Authentication and passwords are handled in master database.
     // This endpoint manages tenant-specific user profile data only.
 
-    let first_name = input.first_name.ok_or_else(|| {
-        error!("Missing first_name in user creation request");
-        (
-            StatusCode::BAD_REQUEST,
-            "First name is required".to_string(),
-        )
-    })?;
-
-    let last_name = input.last_name.ok_or_else(|| {
-        error!("Missing last_name in user creation request");
-        (StatusCode::BAD_REQUEST, "Last name is required".to_string())
-    })?;
-
     info!(
         email = %email,
         first_name = %first_name,
@@ -305,10 +420,7 @@ pub async fn users_create(
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to get tenant database connection");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database connection error".to_string(),
-            )
+            ApiError::from_tenant_connection(e)
         })?;
 
     // Note: Password handling should be done via master database auth endpoints.
@@ -331,17 +443,26 @@ pub async fn users_create(
                 "User created successfully"
             );
 
+            audit_user_change(state, tenant_context, audit::USER_CREATED, &created_user.id, None).await;
+
             let user_response = UserResponse {
                 id: created_user.id,
                 email: created_user.email,
+                display_name: display_name(&created_user.first_name, &created_user.last_name),
                 first_name: created_user.first_name,
                 last_name: created_user.last_name,
                 tenant_id: tenant_context.tenant_id.clone(),
+                version: created_user.version,
+                is_active: created_user.is_active,
                 created_at: created_user.created_at,
                 updated_at: created_user.updated_at,
             };
 
-            Ok((StatusCode::CREATED, Json(user_response)))
+            Ok((StatusCode::CREATED, user_response))
+        }
+        Err(e) if is_unique_violation(&e) => {
+            error!(email = %email, "User creation rejected: email already exists");
+            Err(ApiError::Conflict("A user with this email already exists".to_string()))
         }
         Err(e) => {
             error!(
@@ -349,17 +470,173 @@ pub async fn users_create(
                 email = %email,
                 "Failed to create user in database"
             );
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error".to_string(),
-            ))
+            Err(ApiError::Database(e))
         }
     }
 }
 
+/// Checks the fields a new user needs, returning the email, first name and last name. Every
+/// missing or invalid field is reported, not just the first.
+fn validate_new_user(input: &UsersRequestBody) -> Result<(String, String, String), ApiError> {
+    let mut errors = FieldErrors::new();
+
+    let email = errors.require("email", input.email.as_deref(), "Email is required");
+    if !email.is_empty()
+        && let Err(e) = validate_email(&email)
+    {
+        errors.add("email", e.to_string());
+    }
+    let first_name = errors.require("first_name", input.first_name.as_deref(), "First name is required");
+    let last_name = errors.require("last_name", input.last_name.as_deref(), "Last name is required");
+
+    errors.into_result()?;
+    Ok((email, first_name, last_name))
+}
+
+/// Creates many users in one request.
+///
+/// Every entry is validated before anything is written, and the inserts run in a single
+/// transaction, so the batch is all-or-nothing: if any entry fails, no user is created.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `inputs` - A JSON array of `UsersRequestBody` objects, each with `email`, `first_name`
+///   and `last_name`.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - `201 Created` with the new `id` for every entry, or
+///   `422 Unprocessable Entity` with an `error` on each entry that failed (entries that were
+///   valid but rolled back carry an error saying so). Returns `400 Bad Request` if the batch is
+///   empty or larger than 500 entries.
+#[instrument(skip(state, inputs))]
+pub async fn users_bulk_create(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Json(inputs): Json<Vec<UsersRequestBody>>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_write(&tenant_context).await?;
+    for input in &inputs {
+        ensure_own_tenant(&tenant_context, input.tenant_id.as_deref())?;
+    }
+
+    if inputs.is_empty() {
+        return Err(ApiError::BadRequest("At least one user is required".to_string()));
+    }
+    if inputs.len() > MAX_BULK_USERS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} users can be created at once",
+            MAX_BULK_USERS
+        )));
+    }
+
+    info!(tenant_id = %tenant_context.tenant_id, count = inputs.len(), "Bulk creating users");
+
+    // Validate everything up front, including duplicates within the batch itself
+    let mut seen_emails = HashSet::new();
+    let mut users = Vec::with_capacity(inputs.len());
+    let mut errors: Vec<Option<String>> = Vec::with_capacity(inputs.len());
+    for input in &inputs {
+        match validate_new_user(input) {
+            Ok((email, _, _)) if !seen_emails.insert(email.clone()) => {
+                errors.push(Some(format!("Duplicate email {} in batch", email)));
+            }
+            Ok(fields) => {
+                users.push(fields);
+                errors.push(None);
+            }
+            Err(e) => errors.push(Some(e.to_string())),
+        }
+    }
+
+    if errors.iter().any(Option::is_some) {
+        return Ok(bulk_failure(errors));
+    }
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let txn = tenant_db.begin().await?;
+    let mut ids = Vec::with_capacity(users.len());
+
+    for (index, (email, first_name, last_name)) in users.into_iter().enumerate() {
+        let user = ActiveModel {
+            id: Set(Uuid::new_v4().to_string()),
+            email: Set(email.clone()),
+            first_name: Set(first_name),
+            last_name: Set(last_name),
+            ..Default::default()
+        };
+
+        match user.insert(&txn).await {
+            Ok(created_user) => ids.push(created_user.id),
+            Err(e) => {
+                // Dropping the transaction rolls back the rows inserted so far
+                let message = if is_unique_violation(&e) {
+                    "A user with this email already exists".to_string()
+                } else {
+                    error!(index = index, email = %email, error = %e, "Failed to insert user in bulk create");
+                    "Failed to create user".to_string()
+                };
+                txn.rollback().await?;
+
+                let mut errors = vec![None; inputs.len()];
+                errors[index] = Some(message);
+                return Ok(bulk_failure(errors));
+            }
+        }
+    }
+
+    txn.commit().await?;
+
+    info!(count = ids.len(), "Bulk user creation committed");
+
+    for id in &ids {
+        audit_user_change(&state, &tenant_context, audit::USER_CREATED, id, Some(json!({ "bulk": true }))).await;
+    }
+
+    let results = ids
+        .into_iter()
+        .enumerate()
+        .map(|(index, id)| UsersBulkResult { index, id: Some(id), error: None })
+        .collect();
+
+    Ok((StatusCode::CREATED, Json(UsersBulkResponse { created: true, results })))
+}
+
+/// Builds the `422` response for a rejected batch; entries without an error of their own are
+/// reported as rolled back.
+fn bulk_failure(errors: Vec<Option<String>>) -> (StatusCode, Json<UsersBulkResponse>) {
+    let results = errors
+        .into_iter()
+        .enumerate()
+        .map(|(index, error)| UsersBulkResult {
+            index,
+            id: None,
+            error: Some(error.unwrap_or_else(|| "Not created: batch rolled back".to_string())),
+        })
+        .collect();
+
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(UsersBulkResponse { created: false, results }),
+    )
+}
+
 /// Updates a user by providing a JSON request body with the fields that should be updated.
 ///
-/// The JSON request body should contain the `id` field of the user to be updated.
+/// The JSON request body should contain the `id` field of the user to be updated. If it also
+/// carries a `version`, the update is rejected with `409 Conflict` unless it matches the stored
+/// version, protecting against lost updates. Every successful update increments the version.
 ///
 /// # Arguments
 ///
@@ -373,13 +650,20 @@ pub async fn users_create(
 ///   `200 OK` and serialized JSON data of the updated user.
 #[instrument(skip(state))]
 pub async fn users_update(
-    Extension(state): Extension<AppState>,
-    Extension(tenant_context): Extension<TenantContext>,
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
     Json(updates): Json<UsersRequestBody>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    if let None = updates.id {
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_write(&tenant_context).await?;
+    ensure_own_tenant(&tenant_context, updates.tenant_id.as_deref())?;
+
+    if updates.id.is_none() {
         error!("Missing user ID in update request");
-        return Err((StatusCode::BAD_REQUEST, "User ID is required".to_string()));
+        return Err(ApiError::BadRequest("User ID is required".to_string()));
+    }
+
+    if let Some(email) = &updates.email {
+        validate_email(email)?;
     }
 
     let user_id = updates.id.unwrap();
@@ -392,10 +676,7 @@ pub async fn users_update(
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to get tenant database connection");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database connection error".to_string(),
-            )
+            ApiError::from_tenant_connection(e)
         })?;
 
     let original_user = match Entity::find_by_id(&user_id)
@@ -408,25 +689,37 @@ pub async fn users_update(
         }
         Ok(None) => {
             error!(user_id = user_id, "User not found for update");
-            return Err((
-                StatusCode::NOT_FOUND,
-                "User with provided ID not found".to_string(),
-            ));
+            return Err(ApiError::NotFound("User with provided ID not found".to_string()));
         }
         Err(e) => {
             error!(user_id = user_id, error = %e, "Database error while finding user for update");
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error".to_string(),
-            ));
+            return Err(ApiError::Database(e));
         }
     };
 
+    // Reject the update up front if the client is working from a stale copy.
+    if let Some(expected_version) = updates.version
+        && expected_version != original_user.version
+    {
+        error!(
+            user_id = user_id,
+            expected_version = expected_version,
+            current_version = original_user.version,
+            "Version mismatch for user update"
+        );
+        return Err(ApiError::Conflict(format!(
+                "User has been modified (expected version {}, current version {})",
+                expected_version, original_user.version
+            )));
+    }
+
     let mut user: ActiveModel = original_user.clone().into();
+    let mut changed_fields = Vec::new();
 
     if let Some(email) = updates.email {
         info!(user_id = user_id, email = %email, "Updating email");
         user.email = Set(email);
+        changed_fields.push("email");
     }
 
     // Note: Password updates should be done via master database auth endpoints
@@ -434,14 +727,31 @@ pub async fn users_update(
     if let Some(first_name) = updates.first_name {
         info!(user_id = user_id, first_name = %first_name, "Updating first_name");
         user.first_name = Set(first_name);
+        changed_fields.push("first_name");
     }
 
     if let Some(last_name) = updates.last_name {
         info!(user_id = user_id, last_name = %last_name, "Updating last_name");
         user.last_name = Set(last_name);
+        changed_fields.push("last_name");
     }
 
-    match user.update(&tenant_db).await {
+    // Run the entity hooks (timestamps, version bump) ourselves so the UPDATE can be
+    // guarded on the version we read; a concurrent writer leaves zero rows affected.
+    let user = match user.before_save(&tenant_db, false).await {
+        Ok(user) => user,
+        Err(e) => {
+            error!(user_id = user_id, error = %e, "Failed to prepare user update");
+            return Err(ApiError::Database(e));
+        }
+    };
+
+    let result = Entity::update(user)
+        .filter(Column::Version.eq(original_user.version))
+        .exec(&tenant_db)
+        .await;
+
+    match result {
         Ok(updated_user) => {
             info!(
                 user_id = updated_user.id,
@@ -449,28 +759,45 @@ pub async fn users_update(
                 "User updated successfully"
             );
 
+            audit_user_change(
+                &state,
+                &tenant_context,
+                audit::USER_UPDATED,
+                &updated_user.id,
+                Some(json!({ "fields": changed_fields })),
+            )
+            .await;
+
             let user_response = UserResponse {
                 id: updated_user.id,
                 email: updated_user.email,
+                display_name: display_name(&updated_user.first_name, &updated_user.last_name),
                 first_name: updated_user.first_name,
                 last_name: updated_user.last_name,
                 tenant_id: tenant_context.tenant_id.clone(),
+                version: updated_user.version,
+                is_active: updated_user.is_active,
                 created_at: updated_user.created_at,
                 updated_at: updated_user.updated_at,
             };
 
             Ok((StatusCode::OK, Json(user_response)))
         }
+        Err(e) if is_unique_violation(&e) => {
+            error!(user_id = user_id, "User update rejected: email already exists");
+            Err(ApiError::Conflict("A user with this email already exists".to_string()))
+        }
+        Err(DbErr::RecordNotUpdated) => {
+            error!(user_id = user_id, "User was modified concurrently during update");
+            Err(ApiError::Conflict("User has been modified by another request".to_string()))
+        }
         Err(e) => {
             error!(
                 user_id = user_id,
                 error = %e,
                 "Failed to update user in database"
             );
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error".to_string(),
-            ))
+            Err(ApiError::Database(e))
         }
     }
 }
@@ -492,16 +819,40 @@ pub async fn users_update(
 ///   `200 OK` and a message indicating that the user was deleted successfully.
 #[instrument(skip(state))]
 pub async fn users_delete(
-    Extension(state): Extension<AppState>,
-    Extension(tenant_context): Extension<TenantContext>,
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
     Json(input): Json<UsersRequestBody>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    if let None = input.id {
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_write(&tenant_context).await?;
+    ensure_own_tenant(&tenant_context, input.tenant_id.as_deref())?;
+
+    if input.id.is_none() {
         error!("Missing user ID in delete request");
-        return Err((StatusCode::BAD_REQUEST, "User ID is required".to_string()));
+        return Err(ApiError::BadRequest("User ID is required".to_string()));
     }
 
-    let user_id = input.id.unwrap();
+    delete_user(&state, &tenant_context, input.id.unwrap()).await
+}
+
+/// Deletes a user named in the path, for clients that can't send a body with `DELETE`.
+/// Behaves exactly like `users_delete`.
+#[instrument(skip(state))]
+pub async fn users_delete_by_path(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_write(&tenant_context).await?;
+
+    delete_user(&state, &tenant_context, user_id).await
+}
+
+/// Deletes a user from the tenant database, returning `404` if there is no such user.
+async fn delete_user(
+    state: &AppState,
+    tenant_context: &TenantContext,
+    user_id: String,
+) -> Result<(StatusCode, String), ApiError> {
     info!(user_id = user_id, "Deleting user");
 
     // Get tenant database connection
@@ -511,27 +862,100 @@ pub async fn users_delete(
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to get tenant database connection");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database connection error".to_string(),
-            )
+            ApiError::from_tenant_connection(e)
         })?;
 
     match Entity::delete_by_id(&user_id)
         .exec(&tenant_db)
         .await
     {
+        Ok(result) if result.rows_affected == 0 => {
+            error!(user_id = user_id, "User not found for deletion");
+            Err(ApiError::NotFound("User not found".to_string()))
+        }
         Ok(_) => {
             info!(user_id = user_id, "User deleted successfully");
+            audit_user_change(state, tenant_context, audit::USER_DELETED, &user_id, None).await;
             Ok((StatusCode::OK, "User deleted successfully".to_string()))
         }
         Err(e) => {
             error!(user_id = user_id, error = %e, "Failed to delete user from database");
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error".to_string(),
+            Err(ApiError::Database(e))
+        }
+    }
+}
+
+/// Activates or deactivates a user without deleting it. Like any other update, this bumps the
+/// user's `version`.
+#[instrument(skip(state))]
+pub async fn users_set_active(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Path(user_id): Path<String>,
+    Json(input): Json<UsersSetActiveRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_write(&tenant_context).await?;
+
+    info!(user_id = user_id, is_active = input.is_active, "Setting user activity");
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let user = match Entity::find_by_id(&user_id).one(&tenant_db).await {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            error!(user_id = user_id, "User not found for activity change");
+            return Err(ApiError::NotFound("User not found".to_string()));
+        }
+        Err(e) => {
+            error!(user_id = user_id, error = %e, "Database error while fetching user");
+            return Err(ApiError::Database(e));
+        }
+    };
+
+    let mut user: ActiveModel = user.into();
+    user.is_active = Set(input.is_active);
+
+    match user.update(&tenant_db).await {
+        Ok(updated_user) => {
+            info!(user_id = updated_user.id, is_active = updated_user.is_active, "User activity updated");
+
+            audit_user_change(
+                &state,
+                &tenant_context,
+                audit::USER_UPDATED,
+                &updated_user.id,
+                Some(json!({ "fields": ["is_active"] })),
+            )
+            .await;
+
+            Ok((
+                StatusCode::OK,
+                Json(UserResponse {
+                    id: updated_user.id,
+                    email: updated_user.email,
+                    display_name: display_name(&updated_user.first_name, &updated_user.last_name),
+                    first_name: updated_user.first_name,
+                    last_name: updated_user.last_name,
+                    tenant_id: tenant_context.tenant_id.clone(),
+                    version: updated_user.version,
+                    is_active: updated_user.is_active,
+                    created_at: updated_user.created_at,
+                    updated_at: updated_user.updated_at,
+                }),
             ))
         }
+        Err(e) => {
+            error!(user_id = user_id, error = %e, "Failed to update user activity");
+            Err(ApiError::Database(e))
+        }
     }
 }
 
@@ -552,18 +976,23 @@ pub async fn users_delete(
 ///   `200 OK` and a JSON response with the count of users.
 #[instrument(skip(state))]
 pub async fn users_count(
-    Extension(state): Extension<AppState>,
-    Extension(tenant_context): Extension<TenantContext>,
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
     Query(params): Query<UsersCountUrlParams>,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_read(&tenant_context).await?;
+    ensure_own_tenant(&tenant_context, params.tenant_id.as_deref())?;
+
     info!(
         tenant_id = %tenant_context.tenant_id,
-        email = ?params.email,
-        first_name = ?params.first_name,
-        last_name = ?params.last_name,
+        q = ?params.q,
+        filters = ?params.filters,
         "Counting users"
     );
 
+    let filters = parse_user_filters(&params.filters)?;
+    validate_created_range(params.created_after, params.created_before)?;
+
     // Get tenant database connection
     let tenant_db = state
         .tenant_manager
@@ -571,26 +1000,14 @@ pub async fn users_count(
         .await
         .map_err(|e| {
             error!(error = %e, "Failed to get tenant database connection");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database connection error".to_string(),
-            )
+            ApiError::from_tenant_connection(e)
         })?;
 
-    let mut query = Entity::find();
-
-    // Apply filters
-    if let Some(email) = params.email {
-        query = query.filter(Column::Email.contains(email));
-    }
-    if let Some(first_name) = params.first_name {
-        query = query.filter(Column::FirstName.contains(first_name));
-    }
-    if let Some(last_name) = params.last_name {
-        query = query.filter(Column::LastName.contains(last_name));
-    }
-
-    let count = query.count(&tenant_db).await;
+    let query = apply_user_search(apply_user_filters(Entity::find(), &filters), params.q.as_deref());
+    let query = apply_created_range(query, params.created_after, params.created_before);
+    let count = apply_active_filter(query, params.is_active)
+        .count(&tenant_db)
+        .await;
 
     match count {
         Ok(count_result) => {
@@ -599,10 +1016,194 @@ pub async fn users_count(
         }
         Err(e) => {
             error!(error = %e, "Database error while counting users");
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error".to_string(),
-            ))
+            Err(ApiError::Database(e))
         }
     }
 }
+
+/// Maps a `by` query value to the column it groups on. Only low-cardinality status fields
+/// are allowed; grouping by names, `id` or `email` would return one group per user.
+fn groupable_column(field: &str) -> Option<Column> {
+    match field {
+        "is_active" => Some(Column::IsActive),
+        _ => None,
+    }
+}
+
+/// Counts users grouped by a whitelisted field.
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `params` - A `UsersGroupCountUrlParams` object naming the field to group by.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and a JSON object mapping each distinct value to its user count. Returns
+///   `400 Bad Request` if the field cannot be grouped on.
+#[instrument(skip(state))]
+pub async fn users_group_count(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Query(params): Query<UsersGroupCountUrlParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_read(&tenant_context).await?;
+
+    let column = groupable_column(&params.by).ok_or_else(|| {
+        ApiError::BadRequest(format!(
+            "Cannot group users by '{}'; allowed fields are is_active",
+            params.by
+        ))
+    })?;
+
+    info!(tenant_id = %tenant_context.tenant_id, by = %params.by, "Counting users by group");
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let groups = Entity::find()
+        .select_only()
+        .column_as(Expr::col(column).cast_as(Alias::new("text")), "value")
+        .column_as(Column::Id.count(), "count")
+        .group_by(column)
+        .into_tuple::<(String, i64)>()
+        .all(&tenant_db)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Database error while counting users by group");
+            ApiError::Database(e)
+        })?;
+
+    let counts: BTreeMap<String, i64> = groups.into_iter().collect();
+
+    Ok((StatusCode::OK, Json(counts)))
+}
+
+/// Lists the distinct values of a whitelisted field with their user counts, most common first.
+///
+/// Intended for faceted search, so results are always paginated (`page` defaults to 1 and
+/// `page_size` to 25, clamped to `MAX_PAGE_SIZE`).
+///
+/// # Arguments
+///
+/// * `state` - The application state containing tenant manager.
+/// * `tenant_context` - The tenant context extracted from JWT token.
+/// * `params` - A `UsersDistinctUrlParams` object naming the field and the page to return.
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse>` - If successful, returns an HTTP response with a status code of
+///   `200 OK` and a page of value/count pairs with the total number of distinct values. Returns
+///   `400 Bad Request` if the field is not allowed.
+#[instrument(skip(state))]
+pub async fn users_distinct(
+    State(state): State<AppState>,
+    tenant_context: TenantContext,
+    Query(params): Query<UsersDistinctUrlParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    require_users_read(&tenant_context).await?;
+
+    let column = groupable_column(&params.field).ok_or_else(|| {
+        ApiError::BadRequest(format!(
+            "Cannot list distinct values of '{}'; allowed fields are is_active",
+            params.field
+        ))
+    })?;
+    validate_page(params.page, params.page_size)?;
+    let page = params.page.unwrap_or(1);
+    let page_size = page_size(params.page_size, state.config.max_page_size);
+
+    // Get tenant database connection
+    let tenant_db = state
+        .tenant_manager
+        .get_tenant_connection(&tenant_context.tenant_id)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Failed to get tenant database connection");
+            ApiError::from_tenant_connection(e)
+        })?;
+
+    let paginator = Entity::find()
+        .select_only()
+        .column_as(Expr::col(column).cast_as(Alias::new("text")), "value")
+        .column_as(Column::Id.count(), "count")
+        .group_by(column)
+        .order_by(Column::Id.count(), Order::Desc)
+        .order_by_asc(column)
+        .into_tuple::<(String, i64)>()
+        .paginate(&tenant_db, page_size as u64);
+
+    let total_count = paginator.num_items().await?;
+    let values = paginator
+        .fetch_page((page - 1) as u64)
+        .await
+        .map_err(|e| {
+            error!(error = %e, "Database error while listing distinct user values");
+            ApiError::Database(e)
+        })?
+        .into_iter()
+        .map(|(value, count)| DistinctValueCount { value, count })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(UsersDistinctResponse {
+            field: params.field,
+            values,
+            total_count,
+            page,
+            page_size,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use sea_orm::{DbBackend, QueryTrait};
+    use super::*;
+
+    #[test]
+    fn groups_only_by_low_cardinality_fields() {
+        assert!(matches!(groupable_column("is_active"), Some(Column::IsActive)));
+
+        for field in ["id", "email", "first_name", "last_name", "created_at", ""] {
+            assert!(groupable_column(field).is_none(), "allowed {:?}", field);
+        }
+    }
+
+    fn order_by_clause(sort_by: Option<&str>, sort_dir: Option<&str>) -> String {
+        let sql = sort_users(Entity::find(), sort_by, sort_dir)
+            .unwrap()
+            .build(DbBackend::Postgres)
+            .to_string();
+        sql[sql.find("ORDER BY").unwrap()..].to_string()
+    }
+
+    #[test]
+    fn users_are_ordered_by_id_descending_by_default() {
+        assert_eq!(order_by_clause(None, None), r#"ORDER BY "users"."id" DESC"#);
+        assert_eq!(order_by_clause(Some("id"), Some("asc")), r#"ORDER BY "users"."id" ASC"#);
+    }
+
+    #[test]
+    fn other_sorts_break_ties_by_created_at_then_id() {
+        assert_eq!(
+            order_by_clause(Some("last_name"), Some("asc")),
+            r#"ORDER BY "users"."last_name" ASC, "users"."created_at" ASC, "users"."id" ASC"#
+        );
+        assert_eq!(
+            order_by_clause(Some("created_at"), None),
+            r#"ORDER BY "users"."created_at" DESC, "users"."id" DESC"#
+        );
+        assert!(sort_users(Entity::find(), Some("password"), None).is_err());
+    }
+}
@@ -1,10 +1,65 @@
-use sea_orm::{Database, DatabaseConnection};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use tracing::warn;
 use crate::types::config::DatabaseConfig;
 
+/// Longest pause between connection attempts, however many have failed.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
 pub async fn connect_to_master_database(config: &DatabaseConfig) -> Result<DatabaseConnection, sea_orm::DbErr> {
-    Database::connect(&config.master_url).await
+    connect_with_retry(config, config.connect_options(&config.with_ssl_mode(&config.master_url))).await
+}
+
+pub async fn connect_to_tenant_database(config: &DatabaseConfig, db_url: &str) -> Result<DatabaseConnection, sea_orm::DbErr> {
+    connect_with_retry(config, config.connect_options(db_url)).await
+}
+
+/// Connects with `options`, retrying with backoff per `connect_max_attempts` and
+/// `connect_retry_base_delay_ms` so a database that is briefly down doesn't abort startup.
+pub async fn connect_with_retry(config: &DatabaseConfig, options: ConnectOptions) -> Result<DatabaseConnection, sea_orm::DbErr> {
+    retry_with_backoff(
+        config.connect_max_attempts,
+        Duration::from_millis(config.connect_retry_base_delay_ms),
+        || Database::connect(options.clone()),
+    )
+    .await
 }
 
-pub async fn connect_to_tenant_database(db_url: &str) -> Result<DatabaseConnection, sea_orm::DbErr> {
-    Database::connect(db_url).await
-} 
\ No newline at end of file
+/// Runs `attempt` until it succeeds or has failed `max_attempts` times, returning the last
+/// error. The pause after the n-th failure is `base_delay * 2^(n-1)`, capped at 30 seconds.
+/// `max_attempts` of 0 is treated as 1.
+pub async fn retry_with_backoff<T, E, F, Fut>(
+    max_attempts: u32,
+    base_delay: Duration,
+    mut attempt: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let max_attempts = max_attempts.max(1);
+    let mut delay = base_delay;
+
+    for attempt_number in 1.. {
+        match attempt().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt_number >= max_attempts => return Err(e),
+            Err(e) => {
+                warn!(
+                    attempt = attempt_number,
+                    max_attempts = max_attempts,
+                    retry_in_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "Database connection failed; retrying"
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+    }
+
+    unreachable!("the loop only exits by returning")
+}
@@ -1,10 +1,36 @@
 use sea_orm::{Database, DatabaseConnection};
 use sea_orm_migration::MigratorTrait;
+use tracing::info;
+use crate::types::config::MigrationMode;
 
 pub async fn run_master_migrations(db: &DatabaseConnection) -> Result<(), sea_orm::DbErr> {
     master_migration::MasterMigrator::up(db, None).await
 }
 
+/// Handles pending master migrations according to the configured [`MigrationMode`].
+pub async fn apply_master_migrations(db: &DatabaseConnection, mode: MigrationMode) -> Result<(), sea_orm::DbErr> {
+    match mode {
+        MigrationMode::Auto => run_master_migrations(db).await,
+        MigrationMode::Check => {
+            let pending = master_migration::MasterMigrator::get_pending_migrations(db).await?;
+            if pending.is_empty() {
+                Ok(())
+            } else {
+                let names: Vec<&str> = pending.iter().map(|m| m.name()).collect();
+                Err(sea_orm::DbErr::Custom(format!(
+                    "{} pending master migration(s): {}",
+                    names.len(),
+                    names.join(", ")
+                )))
+            }
+        }
+        MigrationMode::Manual => {
+            info!("MIGRATION_MODE=manual, skipping master migrations");
+            Ok(())
+        }
+    }
+}
+
 pub async fn run_tenant_migrations(db_url: &str) -> Result<(), sea_orm::DbErr> {
     let db = Database::connect(db_url).await?;
     tenant_migration::TenantMigrator::up(&db, None).await
@@ -1,7 +0,0 @@
-//! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.3
-
-pub mod prelude;
-
-pub mod permissions;
-pub mod tenants;
-pub mod users;
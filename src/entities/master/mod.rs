@@ -2,6 +2,11 @@
 
 pub mod prelude;
 
+pub mod api_clients;
+pub mod audit_log;
+pub mod idempotency_keys;
+pub mod password_resets;
 pub mod permissions;
+pub mod revoked_tokens;
 pub mod tenants;
 pub mod users;
@@ -1,5 +1,10 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.3
 
+pub use super::api_clients::Entity as ApiClients;
+pub use super::audit_log::Entity as AuditLog;
+pub use super::idempotency_keys::Entity as IdempotencyKeys;
+pub use super::password_resets::Entity as PasswordResets;
 pub use super::permissions::Entity as Permissions;
+pub use super::revoked_tokens::Entity as RevokedTokens;
 pub use super::tenants::Entity as Tenants;
 pub use super::users::Entity as Users;
@@ -10,16 +10,25 @@ pub struct Model {
     pub id: String,
     pub name: String,
     pub status: String,
+    pub region: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::api_clients::Entity")]
+    ApiClients,
     #[sea_orm(has_many = "super::users::Entity")]
     Users,
 }
 
+impl Related<super::api_clients::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ApiClients.def()
+    }
+}
+
 impl Related<super::users::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Users.def()
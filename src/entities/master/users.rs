@@ -9,7 +9,6 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: String,
     pub tenant_id: String,
-    #[sea_orm(unique)]
     pub email: String,
     pub password_hash: String,
     pub permissions: Json,
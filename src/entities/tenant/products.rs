@@ -10,6 +10,7 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: String,
     pub name: String,
+    pub sku: Option<String>,
     pub description: Option<String>,
     #[sea_orm(column_type = "Decimal(Some((10, 2)))")]
     pub price: Decimal,
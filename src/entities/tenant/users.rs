@@ -1,7 +1,7 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.0
 
 use sea_orm::entity::prelude::*;
-use sea_orm::Set;
+use sea_orm::{ActiveValue, Set};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
@@ -13,6 +13,8 @@ pub struct Model {
     pub email: String,
     pub first_name: String,
     pub last_name: String,
+    pub version: i32,
+    pub is_active: bool,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
@@ -52,8 +54,12 @@ impl ActiveModelBehavior for ActiveModel {
             if insert {
                 self.created_at = Set(now);
                 self.updated_at = Set(now);
+                self.version = Set(1);
             } else {
                 self.updated_at = Set(now);
+                if let ActiveValue::Set(version) | ActiveValue::Unchanged(version) = self.version {
+                    self.version = Set(version + 1);
+                }
             }
             Ok(self)
         })
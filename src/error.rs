@@ -0,0 +1,197 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use sea_orm::{DbErr, RuntimeErr, SqlErr};
+use serde::Serialize;
+use serde_json::json;
+use crate::multi_tenancy::MisdirectedTenant;
+
+/// Error type returned by HTTP handlers.
+///
+/// Every variant renders as `{ "error": { "code": ..., "message": ... } }` with a matching
+/// status code, so clients can rely on a single error shape across endpoints.
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("Unauthorized")]
+    Unauthorized,
+    #[error("Token has expired")]
+    TokenExpired,
+    #[error("Invalid token")]
+    InvalidToken,
+    #[error("Forbidden")]
+    Forbidden,
+    #[error("{0}")]
+    MethodNotAllowed(String),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    UnprocessableEntity(String),
+    /// Every invalid field of a request, so clients can fix them all in one go.
+    #[error("{}", describe_fields(.0))]
+    Validation(Vec<FieldError>),
+    #[error("Too many requests")]
+    TooManyRequests,
+    #[error("Request body is too large")]
+    PayloadTooLarge,
+    /// The tenant's database lives in a region this deployment doesn't serve.
+    #[error("{0}")]
+    MisdirectedRequest(String),
+    /// The request took longer than `REQUEST_TIMEOUT_SECS`.
+    #[error("Request timed out")]
+    GatewayTimeout,
+    #[error("Database error")]
+    Database(#[from] DbErr),
+    #[error("Internal server error")]
+    Internal,
+}
+
+impl ApiError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized | ApiError::TokenExpired | ApiError::InvalidToken => {
+                StatusCode::UNAUTHORIZED
+            }
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::UnprocessableEntity(_) | ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiError::MisdirectedRequest(_) => StatusCode::MISDIRECTED_REQUEST,
+            ApiError::GatewayTimeout => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::Database(_) | ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Stable, machine-readable identifier for the error kind.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "not_found",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Unauthorized => "unauthorized",
+            ApiError::TokenExpired => "token_expired",
+            ApiError::InvalidToken => "invalid_token",
+            ApiError::Forbidden => "forbidden",
+            ApiError::MethodNotAllowed(_) => "method_not_allowed",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::UnprocessableEntity(_) => "unprocessable_entity",
+            ApiError::Validation(_) => "validation_failed",
+            ApiError::TooManyRequests => "rate_limited",
+            ApiError::PayloadTooLarge => "payload_too_large",
+            ApiError::MisdirectedRequest(_) => "misdirected_request",
+            ApiError::GatewayTimeout => "gateway_timeout",
+            ApiError::Database(_) => "database_error",
+            ApiError::Internal => "internal_error",
+        }
+    }
+
+    /// Maps a failure to open a tenant's database connection: 421 when the tenant belongs to
+    /// another region, 500 otherwise.
+    pub fn from_tenant_connection(err: anyhow::Error) -> Self {
+        match err.downcast::<MisdirectedTenant>() {
+            Ok(misdirected) => ApiError::MisdirectedRequest(misdirected.to_string()),
+            Err(_) => ApiError::Internal,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let errors = match &self {
+            ApiError::Validation(errors) => errors.clone(),
+            _ => Vec::new(),
+        };
+
+        // `Display` for `Database` deliberately hides the underlying error from clients
+        let mut body = json!({
+            "error": {
+                "code": self.code(),
+                "message": self.to_string(),
+            }
+        });
+        if !errors.is_empty() {
+            body["errors"] = json!(errors);
+        }
+
+        let mut response = (self.status_code(), Json(body)).into_response();
+        response.extensions_mut().insert(ErrorInfo {
+            code: self.code(),
+            message: self.to_string(),
+            errors,
+        });
+        response
+    }
+}
+
+/// The code and client-facing message of an `ApiError`, attached to its response's
+/// extensions so middleware can re-render the error without parsing the body.
+#[derive(Clone, Debug)]
+pub struct ErrorInfo {
+    pub code: &'static str,
+    pub message: String,
+    /// Per-field failures of a validation error; empty for other errors.
+    pub errors: Vec<FieldError>,
+}
+
+/// One invalid field in a request body.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Joins field messages into the error's one-line `message`.
+fn describe_fields(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl ErrorInfo {
+    /// RFC 7807 `type` URI identifying this kind of error.
+    pub fn problem_type(&self) -> String {
+        format!("/problems/{}", self.code.replace('_', "-"))
+    }
+}
+
+/// Returns true when `err` is a unique-constraint violation (Postgres SQLSTATE `23505`).
+pub fn is_unique_violation(err: &DbErr) -> bool {
+    matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_)))
+}
+
+/// The constraint or index a unique violation tripped, e.g. `idx_products_sku`.
+pub fn unique_violation_constraint(err: &DbErr) -> Option<&str> {
+    if !is_unique_violation(err) {
+        return None;
+    }
+    match err {
+        DbErr::Exec(RuntimeErr::SqlxError(e)) | DbErr::Query(RuntimeErr::SqlxError(e)) => {
+            e.as_database_error()?.constraint()
+        }
+        _ => None,
+    }
+}
+
+/// Returns true when `err` is a foreign-key violation (Postgres SQLSTATE `23503`).
+pub fn is_foreign_key_violation(err: &DbErr) -> bool {
+    matches!(err.sql_err(), Some(SqlErr::ForeignKeyConstraintViolation(_)))
+}
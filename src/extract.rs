@@ -0,0 +1,52 @@
+use axum::{
+    async_trait,
+    extract::{FromRequest, FromRequestParts, rejection::JsonRejection},
+    http::{StatusCode, request::Parts},
+    response::{IntoResponse, Response},
+};
+use crate::{error::ApiError, types::shared::TenantContext};
+
+/// Drop-in replacement for `axum::Json` whose rejections are `ApiError`s, so malformed or
+/// oversized bodies get the same error shape as every other failure.
+///
+/// Deserialization failures become `400`s that name the offending field; bodies over the
+/// `MAX_BODY_BYTES` limit become `413`s.
+#[derive(Debug, Clone, Copy, Default, FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct Json<T>(pub T);
+
+impl<T> IntoResponse for Json<T>
+where
+    axum::Json<T>: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => ApiError::PayloadTooLarge,
+            _ => ApiError::BadRequest(rejection.body_text()),
+        }
+    }
+}
+
+/// The caller's tenant context, as attached by `auth_middleware`. Rejects with `401` when the
+/// request didn't pass through it, rather than the `500` a missing `Extension` would give.
+#[async_trait]
+impl<S> FromRequestParts<S> for TenantContext
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<TenantContext>()
+            .cloned()
+            .ok_or(ApiError::Unauthorized)
+    }
+}
@@ -0,0 +1,193 @@
+//! `Idempotency-Key` support for endpoints that create things, so a client retrying after a
+//! timeout gets the original response instead of a duplicate.
+//!
+//! The first request with a key claims it in the master `idempotency_keys` table, runs, and
+//! stores its response there; later requests with the same key replay that response. Keys are
+//! scoped per tenant.
+
+use std::future::Future;
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tracing::error;
+use crate::{error::ApiError, extract::Json, multi_tenancy::MasterService};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Longest accepted `Idempotency-Key` value.
+const MAX_KEY_LENGTH: usize = 255;
+
+/// A key still unfinished after this long is assumed to belong to a request that died
+/// mid-flight, and the next request with the key may take it over.
+const ABANDONED_AFTER: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Hash identifying a request, so a key reused for a different request can be told apart from
+/// a retry. `operation` distinguishes endpoints that share a scope.
+pub fn request_fingerprint(operation: &str, request: &impl Serialize) -> String {
+    let mut body = serde_json::to_value(request).unwrap_or(Value::Null);
+    // A plain SHA-256 over a password would be cheap to brute-force from the stored hash
+    if let Value::Object(fields) = &mut body {
+        fields.remove("password");
+    }
+
+    let digest = Sha256::new()
+        .chain_update(operation)
+        .chain_update([0])
+        .chain_update(body.to_string())
+        .finalize();
+    format!("{:x}", digest)
+}
+
+/// Reads the `Idempotency-Key` header; `None` when the client didn't send one.
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, ApiError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+
+    let key = value.to_str().map(str::trim).unwrap_or_default();
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(ApiError::BadRequest(format!(
+            "{} must be 1 to {} visible ASCII characters",
+            IDEMPOTENCY_KEY_HEADER, MAX_KEY_LENGTH
+        )));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
+/// Runs `create` once per `Idempotency-Key` within `scope`, replaying the stored response for
+/// repeats. Without the header, `create` simply runs.
+///
+/// Repeats of a key still in progress get `409`, and a key reused with a different
+/// `fingerprint` gets `422`. Failed requests aren't stored, so they can be retried with the
+/// same key.
+pub async fn idempotent<T, Fut>(
+    master_service: &MasterService,
+    headers: &HeaderMap,
+    scope: &str,
+    fingerprint: String,
+    create: Fut,
+) -> Result<Response, ApiError>
+where
+    T: Serialize,
+    Fut: Future<Output = Result<(StatusCode, T), ApiError>>,
+{
+    let Some(key) = idempotency_key(headers)? else {
+        let (status, body) = create.await?;
+        return Ok((status, Json(body)).into_response());
+    };
+
+    // The claim is a single insert on the key's primary key, so of two concurrent requests
+    // with the same key exactly one proceeds
+    let abandoned_before = Utc::now().naive_utc() - ABANDONED_AFTER;
+    if !master_service.claim_idempotency_key(scope, &key, &fingerprint, abandoned_before).await? {
+        return replay(master_service, scope, &key, &fingerprint).await;
+    }
+
+    match create.await {
+        Ok((status, body)) => {
+            let body = serde_json::to_value(body).map_err(|e| {
+                error!(error = %e, "Failed to serialize response for idempotency key");
+                ApiError::Internal
+            })?;
+            if let Err(e) = master_service.complete_idempotency_key(scope, &key, status.as_u16(), &body).await {
+                // The work is done, so the client still gets its response; retries with the key
+                // will see it as in progress until it is abandoned
+                error!(scope = %scope, key = %key, error = %e, "Failed to store idempotent response");
+            }
+            Ok((status, Json(body)).into_response())
+        }
+        Err(e) => {
+            if let Err(release_error) = master_service.release_idempotency_key(scope, &key).await {
+                error!(scope = %scope, key = %key, error = %release_error, "Failed to release idempotency key");
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Answers a repeat of an already claimed key.
+async fn replay(master_service: &MasterService, scope: &str, key: &str, fingerprint: &str) -> Result<Response, ApiError> {
+    let in_progress = || ApiError::Conflict("A request with this Idempotency-Key is still in progress".to_string());
+
+    // Missing only if the original request failed and released the key since the claim
+    let record = master_service.idempotency_record(scope, key).await?.ok_or_else(in_progress)?;
+
+    if record.request_hash != fingerprint {
+        return Err(ApiError::UnprocessableEntity(
+            "Idempotency-Key was already used for a different request".to_string(),
+        ));
+    }
+
+    match (record.status_code, record.response_body) {
+        (Some(status_code), Some(body)) => {
+            let status = StatusCode::from_u16(status_code as u16).map_err(|_| ApiError::Internal)?;
+            Ok((status, Json(body)).into_response())
+        }
+        _ => Err(in_progress()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use serde_json::json;
+    use super::*;
+
+    fn headers(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_str(key).unwrap());
+        headers
+    }
+
+    #[test]
+    fn reads_the_key_trimmed() {
+        assert_eq!(idempotency_key(&HeaderMap::new()).unwrap(), None);
+        assert_eq!(idempotency_key(&headers(" retry-1 ")).unwrap().as_deref(), Some("retry-1"));
+        assert!(idempotency_key(&headers(&"k".repeat(MAX_KEY_LENGTH))).is_ok());
+    }
+
+    #[test]
+    fn rejects_empty_long_and_non_ascii_keys() {
+        assert!(idempotency_key(&headers("   ")).is_err());
+        assert!(idempotency_key(&headers(&"k".repeat(MAX_KEY_LENGTH + 1))).is_err());
+
+        let mut non_ascii = HeaderMap::new();
+        non_ascii.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_bytes("clé".as_bytes()).unwrap());
+        assert!(idempotency_key(&non_ascii).is_err());
+    }
+
+    #[test]
+    fn fingerprint_matches_only_the_same_request() {
+        let request = json!({ "email": "jane@example.com", "first_name": "Jane" });
+        let fingerprint = request_fingerprint("users.create", &request);
+
+        assert_eq!(request_fingerprint("users.create", &request), fingerprint);
+        assert_eq!(fingerprint.len(), 64);
+        assert_ne!(request_fingerprint("users.register", &request), fingerprint);
+        assert_ne!(
+            request_fingerprint("users.create", &json!({ "email": "jane@example.com", "first_name": "Janet" })),
+            fingerprint
+        );
+    }
+
+    #[test]
+    fn fingerprint_leaves_out_the_password() {
+        let with_password = json!({ "email": "jane@example.com", "password": "hunter2-hunter2" });
+        let other_password = json!({ "email": "jane@example.com", "password": "correct-horse" });
+
+        assert_eq!(
+            request_fingerprint("users.create", &with_password),
+            request_fingerprint("users.create", &other_password)
+        );
+        assert_eq!(
+            request_fingerprint("users.create", &with_password),
+            request_fingerprint("users.create", &json!({ "email": "jane@example.com" }))
+        );
+    }
+}
@@ -5,6 +5,14 @@ pub mod middlewares;
 pub mod database;
 pub mod multi_tenancy;
 pub mod entities;
+pub mod error;
+pub mod validation;
+pub mod permissions;
+pub mod audit;
+pub mod pagination;
+pub mod extract;
+pub mod idempotency;
+pub mod logging;
 
 // Re-export specific items from each module to avoid conflicts
 pub use types::{
@@ -13,6 +21,7 @@ pub use types::{
     UsersUrlParams, UsersCountUrlParams, UsersRequestBody, UsersResponseType, UserResponse,
     AppConfig, DatabaseConfig
 };
+pub use error::ApiError;
 pub use database::{connect_to_master_database, connect_to_tenant_database};
 pub use multi_tenancy::{TenantConnectionManager, MasterService, TenantService};
 pub use middlewares::{auth_middleware, create_jwt_token}; 
\ No newline at end of file
@@ -0,0 +1,26 @@
+use tracing_subscriber::{
+    EnvFilter, fmt,
+    util::{SubscriberInitExt, TryInitError},
+};
+use crate::types::config::LogFormat;
+
+/// Level filter used when `RUST_LOG` is unset or invalid.
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Installs the global tracing subscriber, writing to stdout in `format` at the levels given
+/// by `RUST_LOG`. Fails if a subscriber is already installed.
+pub fn init_tracing(format: LogFormat) -> Result<(), TryInitError> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_LOG_FILTER));
+    let builder = fmt().with_env_filter(filter);
+
+    match format {
+        LogFormat::Pretty => builder.finish().try_init(),
+        // The current span and its parents carry the request id and tenant of each log line
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .finish()
+            .try_init(),
+    }
+}
@@ -1,10 +1,23 @@
-use axum::{Router, middleware};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use axum::{Router, extract::DefaultBodyLimit, middleware};
 use dotenv::dotenv;
+use tokio::sync::Notify;
+use tower_http::{compression::CompressionLayer, timeout::TimeoutLayer};
+use tracing::{info, warn};
 use rust_multi_tenant::{
-    database::{connect_to_master_database, run_master_migrations},
-    middlewares::{auth_middleware, create_cors_layer},
-    multi_tenancy::TenantConnectionManager,
-    routes::{auth_routes, tenant_routes, user_routes},
+    database::{apply_master_migrations, connect_to_master_database},
+    logging::init_tracing,
+    middlewares::{
+        InFlightRequests, RateLimiter, RevokedTokens, auth_middleware, create_cors_layer, https_enforcement_middleware,
+        install_metrics_recorder, log_request_body,
+        problem_json_middleware, request_id_middleware, request_timeout_middleware, response_envelope_middleware,
+        track_in_flight, track_metrics,
+    },
+    controllers::{method_not_allowed, route_not_found},
+    multi_tenancy::{MasterService, TenantConnectionManager, audit_retention_cutoff},
+    routes::{account_routes, admin_routes, auth_routes, order_routes, product_routes, tenant_routes, user_routes},
     types::config::AppConfig,
     types::shared::AppState,
 };
@@ -15,37 +28,244 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Load configuration
     let config = AppConfig::from_env()?;
+    init_tracing(config.log_format)?;
 
     // Initialize tenant manager
-    let tenant_manager = TenantConnectionManager::new(config.database_config.clone()).await?;
+    let tenant_manager = TenantConnectionManager::new(config.database_config.clone())
+        .await?
+        .with_product_unique_key(config.product_unique_key);
 
-    // Run master migrations
+    // Run (or check) master migrations according to MIGRATION_MODE
     let master_db = connect_to_master_database(&config.database_config).await?;
-    run_master_migrations(&master_db).await?;
+    apply_master_migrations(&master_db, config.migration_mode).await?;
+
+    // Make sure the permission catalog knows every built-in permission
+    let seeded = MasterService::new(master_db).seed_default_permissions().await?;
+    if seeded > 0 {
+        info!(count = seeded, "Seeded default permissions");
+    }
+
+    // Open tenant pools now so early requests don't pay for connection setup
+    if config.warmup_tenants {
+        let warmed = tenant_manager.warmup_active_tenants().await?;
+        info!(tenants = warmed, "Warmed up tenant connections");
+    }
+
+    let metrics = install_metrics_recorder()?;
+
+    // Load revocations before serving, so revoked tokens are rejected from the first request
+    let revoked_tokens = RevokedTokens::new();
+    revoked_tokens
+        .refresh(&MasterService::new(tenant_manager.get_master_connection().await))
+        .await?;
 
     let state = AppState {
         tenant_manager,
-        jwt_secret: config.jwt_secret,
+        password_policy: config.password_policy(),
+        max_token_permissions: config.max_token_permissions,
+        product_unique_key: config.product_unique_key,
+        config: Arc::new(config.clone()),
+        jwt_keys: config.jwt_keys()?,
+        jwt_expiration: config.jwt_expiration,
+        rate_limiter: RateLimiter::new(20, Duration::from_secs(60)),
+        revoked_tokens,
+        metrics,
     };
 
+    // Periodically report tenants whose schema has drifted from the migrations
+    if let Some(secs) = config.schema_drift_check_interval_secs {
+        let tenant_manager = state.tenant_manager.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(secs));
+            loop {
+                interval.tick().await;
+                match tenant_manager.detect_drift_all().await {
+                    Ok(report) => {
+                        for (tenant_id, drift) in report {
+                            warn!(tenant_id = %tenant_id, drift = ?drift, "Tenant schema drift detected");
+                        }
+                    }
+                    Err(e) => warn!(error = %e, "Schema drift sweep failed"),
+                }
+            }
+        });
+    }
+
+    // Keep the audit log within its retention window; each purge batch commits on its own, so
+    // stopping mid-run loses nothing
+    if let Some(retention_days) = config.audit_retention_days {
+        let tenant_manager = state.tenant_manager.clone();
+        let secs = config.audit_purge_interval_secs;
+        let batch_size = config.audit_purge_batch_size;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(secs));
+            loop {
+                interval.tick().await;
+                let master_service = MasterService::new(tenant_manager.get_master_connection().await);
+                let older_than = audit_retention_cutoff(chrono::Utc::now().naive_utc(), retention_days);
+                match master_service.purge_audit_log_all(older_than, batch_size).await {
+                    Ok(purged) => info!(purged, retention_days, "Audit retention purge finished"),
+                    Err(e) => warn!(error = %e, "Audit retention purge failed"),
+                }
+            }
+        });
+    }
+
+    // Pick up tokens revoked through other instances, and drop revocations that have expired
+    {
+        let tenant_manager = state.tenant_manager.clone();
+        let revoked_tokens = state.revoked_tokens.clone();
+        let secs = config.revoked_tokens_refresh_secs;
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(secs));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let master_service = MasterService::new(tenant_manager.get_master_connection().await);
+                if let Err(e) = master_service.purge_expired_revoked_tokens().await {
+                    warn!(error = %e, "Failed to purge expired revoked tokens");
+                }
+                if let Err(e) = revoked_tokens.refresh(&master_service).await {
+                    warn!(error = %e, "Failed to refresh revoked tokens");
+                }
+            }
+        });
+    }
+
     // Create CORS layer
     let cors = create_cors_layer();
 
-    let app = Router::new()
-        .merge(auth_routes())
+    // Routes that require a valid JWT; auth and health routes stay public
+    let protected_routes = Router::new()
+        .merge(account_routes())
         .merge(user_routes())
-        .merge(tenant_routes())
-        .layer(middleware::from_fn_with_state(
+        .merge(product_routes())
+        .merge(order_routes())
+        .merge(admin_routes())
+        .route_layer(middleware::from_fn_with_state(
             state.clone(),
             auth_middleware,
+        ));
+
+    let mut app = Router::new()
+        .merge(auth_routes())
+        .merge(tenant_routes())
+        .merge(protected_routes)
+        // Unknown paths and methods answer with the same JSON error shape as handlers
+        .method_not_allowed_fallback(method_not_allowed)
+        .fallback(route_not_found)
+        // Bounds handlers stuck on a slow tenant database; the timeout's bare 408 becomes a 504
+        .layer(TimeoutLayer::new(Duration::from_secs(config.request_timeout_secs)))
+        .layer(middleware::from_fn(request_timeout_middleware));
+
+    // Development aid only: never enable in production
+    if config.log_request_bodies {
+        app = app.layer(middleware::from_fn(log_request_body));
+    }
+
+    if config.response_envelope {
+        app = app.layer(middleware::from_fn(response_envelope_middleware));
+    }
+
+    // Always layered: clients can ask for problem+json via Accept even when the flag is off
+    let app = app.layer(middleware::from_fn_with_state(
+        config.problem_json_errors,
+        problem_json_middleware,
+    ));
+
+    // Reject plain HTTP before any other work; only a trusted proxy can vouch for HTTPS
+    let app = if config.require_https {
+        if !config.trust_proxy {
+            warn!("REQUIRE_HTTPS is set without TRUST_PROXY; every request will be treated as plain HTTP");
+        }
+        app.layer(middleware::from_fn_with_state(
+            config.trust_proxy,
+            https_enforcement_middleware,
         ))
+    } else {
+        app
+    };
+
+    let tenant_manager = state.tenant_manager.clone();
+    let in_flight = InFlightRequests::default();
+
+    // Outermost, so every other layer and handler sees the request id
+    let app = app
+        .layer(DefaultBodyLimit::max(config.max_body_bytes))
+        // Outside the envelope and problem+json layers, which need to read uncompressed bodies
+        .layer(CompressionLayer::new())
+        .layer(middleware::from_fn(track_metrics))
         .layer(cors)
+        .layer(middleware::from_fn_with_state(in_flight.clone(), track_in_flight))
+        .layer(middleware::from_fn(request_id_middleware))
         .with_state(state);
 
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
+    let listener = tokio::net::TcpListener::bind((config.bind_address.as_str(), config.port)).await?;
+    let local_addr = listener.local_addr()?;
 
-    println!("🚀 Multi-tenant API server running on http://0.0.0.0:8000");
-    axum::serve(listener, app).await.unwrap();
+    info!(address = %local_addr, "Listening");
+    println!("🚀 Multi-tenant API server running on http://{}", local_addr);
+    let shutdown = Arc::new(Notify::new());
+    let mut server = tokio::spawn({
+        let shutdown = shutdown.clone();
+        axum::serve(
+            listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(async move { shutdown.notified().await })
+        .into_future()
+    });
+
+    tokio::select! {
+        result = &mut server => result??,
+        _ = shutdown_signal() => {
+            shutdown.notify_one();
+
+            // Give in-flight requests a bounded window so a stuck request can't hang shutdown
+            let drain_timeout = Duration::from_secs(config.shutdown_drain_timeout_secs);
+            match tokio::time::timeout(drain_timeout, &mut server).await {
+                Ok(result) => result??,
+                Err(_) => {
+                    warn!(
+                        in_flight = in_flight.count(),
+                        timeout_secs = config.shutdown_drain_timeout_secs,
+                        "Shutdown drain timeout elapsed; cutting off in-flight requests"
+                    );
+                    server.abort();
+                }
+            }
+        }
+    }
+
+    // Requests have finished or been cut off; release database connections cleanly
+    tenant_manager.close_all().await;
 
     Ok(())
 }
+
+/// Resolves on Ctrl+C (SIGINT) or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received, draining in-flight requests");
+}
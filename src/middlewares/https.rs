@@ -0,0 +1,49 @@
+use axum::{
+    extract::{Request, State},
+    http::{Method, header},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+use crate::error::ApiError;
+
+/// Header set by TLS-terminating proxies to the scheme the client used.
+pub const FORWARDED_PROTO_HEADER: &str = "x-forwarded-proto";
+
+/// Rejects plain-HTTP requests; layered only when `REQUIRE_HTTPS` is set.
+///
+/// Safe requests (`GET`/`HEAD`) are redirected with `301` to the same URL over HTTPS; anything
+/// else gets `403`, since redirecting would drop the body. The server itself never terminates
+/// TLS, so `X-Forwarded-Proto` is the only evidence of HTTPS and is honoured only when the state
+/// flag (`TRUST_PROXY`) says a proxy sets it. Without it, every request counts as plain HTTP.
+pub async fn https_enforcement_middleware(
+    State(trust_proxy): State<bool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_https = trust_proxy
+        && request
+            .headers()
+            .get(FORWARDED_PROTO_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"));
+
+    if is_https {
+        return next.run(request).await;
+    }
+
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+
+    match (request.method(), host) {
+        (&Method::GET | &Method::HEAD, Some(host)) => {
+            let path = request
+                .uri()
+                .path_and_query()
+                .map_or("/", |path| path.as_str());
+            Redirect::permanent(&format!("https://{}{}", host, path)).into_response()
+        }
+        _ => ApiError::Forbidden.into_response(),
+    }
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+/// Counts requests currently being handled, so shutdown can report how many were cut off.
+#[derive(Clone, Debug, Default)]
+pub struct InFlightRequests(Arc<AtomicUsize>);
+
+impl InFlightRequests {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Decrements the counter when dropped, including when the request future is cancelled.
+struct InFlightGuard(InFlightRequests);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub async fn track_in_flight(
+    State(in_flight): State<InFlightRequests>,
+    request: Request,
+    next: Next,
+) -> Response {
+    in_flight.0.fetch_add(1, Ordering::Relaxed);
+    let _guard = InFlightGuard(in_flight);
+    next.run(request).await
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::time::Instant;
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use metrics_exporter_prometheus::{BuildError, Matcher, PrometheusBuilder, PrometheusHandle};
+
+/// Histogram buckets for request latency, from 5ms to 10s.
+const LATENCY_BUCKETS_SECS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Renders the process-wide Prometheus recorder installed by `install_metrics_recorder`.
+#[derive(Clone)]
+pub struct MetricsHandle(PrometheusHandle);
+
+impl MetricsHandle {
+    /// Current metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        self.0.render()
+    }
+}
+
+impl fmt::Debug for MetricsHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricsHandle").finish_non_exhaustive()
+    }
+}
+
+/// Installs the global metrics recorder. Fails if one is already installed, so call it once
+/// at startup.
+pub fn install_metrics_recorder() -> Result<MetricsHandle, BuildError> {
+    PrometheusBuilder::new()
+        .set_buckets_for_metric(
+            Matcher::Full("http_request_duration_seconds".to_string()),
+            LATENCY_BUCKETS_SECS,
+        )?
+        .install_recorder()
+        .map(MetricsHandle)
+}
+
+/// Counts each request and records its latency, labelled by method, route template and status.
+///
+/// The route is the matched template (e.g. `/tenants/:tenant_id`), never the raw path, so ids
+/// don't end up as label values; requests that match no route are labelled `unmatched`.
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let labels = [
+        ("method", method),
+        ("route", route),
+        ("status", response.status().as_u16().to_string()),
+    ];
+    metrics::counter!("http_requests_total", &labels).increment(1);
+    metrics::histogram!("http_request_duration_seconds", &labels)
+        .record(started.elapsed().as_secs_f64());
+
+    response
+}
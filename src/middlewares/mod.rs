@@ -1,5 +1,25 @@
 pub mod auth;
 pub mod cors;
+pub mod rate_limit;
+pub mod request_logging;
+pub mod request_id;
+pub mod response_envelope;
+pub mod in_flight;
+pub mod problem_json;
+pub mod https;
+pub mod metrics;
+pub mod token_revocation;
+pub mod timeout;
 
 pub use auth::*;
-pub use cors::*; 
\ No newline at end of file
+pub use cors::*;
+pub use rate_limit::*;
+pub use request_logging::*;
+pub use request_id::*;
+pub use response_envelope::*;
+pub use in_flight::*;
+pub use problem_json::*;
+pub use https::*;
+pub use metrics::*;
+pub use token_revocation::*;
+pub use timeout::*; 
\ No newline at end of file
@@ -0,0 +1,60 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::json;
+use crate::error::ErrorInfo;
+
+/// Media type for RFC 7807 problem details.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Renders error responses as RFC 7807 problem details
+/// (`{ "type", "title", "status", "detail", "instance" }`).
+///
+/// Applies to every error when the state flag (`PROBLEM_JSON_ERRORS`) is set, and otherwise
+/// only to requests whose `Accept` header asks for `application/problem+json`. Errors that
+/// didn't come from `ApiError` (e.g. a bare `401` from the auth middleware) get the
+/// `about:blank` type and no `detail`. Successful responses are untouched.
+pub async fn problem_json_middleware(
+    State(always): State<bool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let requested = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains(PROBLEM_JSON));
+    let instance = request.uri().path().to_string();
+
+    let response = next.run(request).await;
+
+    let status = response.status();
+    if !(always || requested) || !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let (mut parts, _) = response.into_parts();
+    let info = parts.extensions.get::<ErrorInfo>();
+
+    let mut problem = json!({
+        "type": info.map_or_else(|| "about:blank".to_string(), ErrorInfo::problem_type),
+        "title": status.canonical_reason().unwrap_or("Error"),
+        "status": status.as_u16(),
+        "detail": info.map(|info| info.message.clone()),
+        "instance": instance,
+    });
+    // Per-field validation failures travel as an RFC 7807 extension member
+    if let Some(info) = info.filter(|info| !info.errors.is_empty()) {
+        problem["errors"] = json!(info.errors);
+    }
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    Response::from_parts(parts, Body::from(problem.to_string()))
+}
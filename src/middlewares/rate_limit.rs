@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Fixed-window, in-memory rate limiter keyed by an arbitrary string (usually the client IP).
+///
+/// Counters are per process, so limits apply per instance rather than cluster-wide.
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    windows: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+    max_requests: u32,
+    window: Duration,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        Self {
+            windows: Arc::new(Mutex::new(HashMap::new())),
+            max_requests,
+            window,
+        }
+    }
+
+    /// Records a request for `key` and returns whether it is within the limit.
+    pub fn check(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Drop expired windows so the map doesn't grow with every client ever seen
+        if windows.len() > 10_000 {
+            windows.retain(|_, (started, _)| now.duration_since(*started) < self.window);
+        }
+
+        let entry = windows.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+
+        entry.1 += 1;
+        entry.1 <= self.max_requests
+    }
+}
@@ -0,0 +1,49 @@
+use axum::{
+    extract::Request,
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use tracing::{Instrument, field, info_span};
+use uuid::Uuid;
+
+/// Header used to receive and return the request id.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Identifier of the current request, available to handlers and inner middleware as an extension.
+#[derive(Clone, Debug)]
+pub struct RequestId(pub String);
+
+/// Assigns every request an id and echoes it back in the `X-Request-ID` response header.
+///
+/// A client-supplied id is kept when it is a reasonable length and printable ASCII, so
+/// callers can correlate their own logs; otherwise a UUID is generated. The rest of the
+/// request runs inside a `request` span carrying the id; `auth_middleware` fills in the
+/// span's `tenant_id` once the token has been validated.
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    // Ids that passed `to_str` above and generated UUIDs are both valid header values
+    let header_value = HeaderValue::from_str(&request_id).expect("request id is a valid header value");
+
+    let span = info_span!(
+        "request",
+        request_id = %request_id,
+        method = %request.method(),
+        uri = %request.uri(),
+        tenant_id = field::Empty,
+    );
+
+    request.headers_mut().insert(REQUEST_ID_HEADER, header_value.clone());
+    request.extensions_mut().insert(RequestId(request_id));
+
+    let mut response = next.run(request).instrument(span).await;
+    response.headers_mut().insert(REQUEST_ID_HEADER, header_value);
+    response
+}
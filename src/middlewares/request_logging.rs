@@ -0,0 +1,70 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use tracing::debug;
+
+/// Bodies larger than this are rejected rather than buffered for logging.
+const MAX_LOGGED_BODY_BYTES: usize = 1024 * 1024;
+
+/// Any JSON key containing one of these fragments has its value replaced before logging.
+const SENSITIVE_KEY_FRAGMENTS: &[&str] = &["password", "token", "secret", "authorization", "api_key"];
+
+const MASK: &str = "***";
+
+/// Logs request bodies at debug level with sensitive fields masked.
+///
+/// Only layered when `LOG_REQUEST_BODIES` is enabled. The body is buffered, logged, and
+/// handed on to the next service unchanged.
+pub async fn log_request_body(request: Request, next: Next) -> Result<Response, StatusCode> {
+    let (parts, body) = request.into_parts();
+
+    let bytes = to_bytes(body, MAX_LOGGED_BODY_BYTES)
+        .await
+        .map_err(|_| StatusCode::PAYLOAD_TOO_LARGE)?;
+
+    if !bytes.is_empty() {
+        debug!(
+            method = %parts.method,
+            uri = %parts.uri,
+            body = %sanitize_body(&bytes),
+            "Request body"
+        );
+    }
+
+    let request = Request::from_parts(parts, Body::from(bytes));
+    Ok(next.run(request).await)
+}
+
+/// Renders a request body for logging, masking sensitive JSON fields. Non-JSON bodies are
+/// summarised by size only, since they can't be sanitized reliably.
+pub fn sanitize_body(bytes: &[u8]) -> String {
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(mut value) => {
+            mask_sensitive_fields(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes of non-JSON body omitted>", bytes.len()),
+    }
+}
+
+fn mask_sensitive_fields(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                let key = key.to_ascii_lowercase();
+                if SENSITIVE_KEY_FRAGMENTS.iter().any(|fragment| key.contains(fragment)) {
+                    *field = Value::String(MASK.to_string());
+                } else {
+                    mask_sensitive_fields(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask_sensitive_fields),
+        _ => {}
+    }
+}
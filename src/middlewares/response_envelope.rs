@@ -0,0 +1,58 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use chrono::Utc;
+use serde_json::{Value, json};
+use tracing::warn;
+use super::RequestId;
+
+/// Wraps successful JSON responses as `{ "data": ..., "meta": { "request_id", "timestamp" } }`.
+///
+/// Only layered when `RESPONSE_ENVELOPE` is enabled, and must run inside
+/// `request_id_middleware` so the request id is available. Error responses and non-JSON
+/// bodies pass through untouched, keeping the error shape identical in both modes.
+pub async fn response_envelope_middleware(request: Request, next: Next) -> Response {
+    let request_id = request.extensions().get::<RequestId>().map(|id| id.0.clone());
+
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    if !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!(error = %e, "Failed to buffer response body for envelope");
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let data: Value = match serde_json::from_slice(&bytes) {
+        Ok(data) => data,
+        Err(_) => return Response::from_parts(parts, Body::from(bytes)),
+    };
+
+    let enveloped = json!({
+        "data": data,
+        "meta": {
+            "request_id": request_id,
+            "timestamp": Utc::now().to_rfc3339(),
+        }
+    });
+
+    // The body length changed, so let the server recompute it
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(enveloped.to_string()))
+}
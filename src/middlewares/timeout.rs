@@ -0,0 +1,72 @@
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use crate::error::{ApiError, ErrorInfo};
+
+/// Replaces the empty `408` that `TimeoutLayer` answers with when a request runs past
+/// `REQUEST_TIMEOUT_SECS` by a structured `504`.
+///
+/// Must be layered directly outside the timeout layer. Handlers never return `408`
+/// themselves, and their errors carry `ErrorInfo`, so only timeouts are rewritten.
+pub async fn request_timeout_middleware(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+
+    if response.status() == StatusCode::REQUEST_TIMEOUT && response.extensions().get::<ErrorInfo>().is_none() {
+        return ApiError::GatewayTimeout.into_response();
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use axum::{Router, middleware, routing::get};
+    use serde_json::Value;
+    use tower_http::timeout::TimeoutLayer;
+    use super::*;
+
+    /// Serves the two test routes behind the timeout layers, wired as in `main`, and returns
+    /// the base URL.
+    async fn serve() -> String {
+        let app = Router::new()
+            .route("/fast", get(|| async { "done" }))
+            .route("/slow", get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "done"
+            }))
+            .route("/conflict", get(|| async { ApiError::Conflict("taken".to_string()) }))
+            .layer(TimeoutLayer::new(Duration::from_millis(50)))
+            .layer(middleware::from_fn(request_timeout_middleware));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn timed_out_request_gets_a_structured_504() {
+        let base = serve().await;
+
+        let response = reqwest::get(format!("{}/slow", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "gateway_timeout");
+    }
+
+    #[tokio::test]
+    async fn other_responses_pass_through() {
+        let base = serve().await;
+
+        let response = reqwest::get(format!("{}/fast", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "done");
+
+        let response = reqwest::get(format!("{}/conflict", base)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use chrono::{NaiveDateTime, Utc};
+use crate::multi_tenancy::MasterService;
+
+/// In-memory copy of the `revoked_tokens` table, checked by `auth_middleware` on every request
+/// so revocation doesn't cost a database query per request.
+///
+/// Each instance refreshes its copy periodically, so a token revoked through another instance
+/// is rejected here within one refresh interval.
+#[derive(Clone, Debug, Default)]
+pub struct RevokedTokens {
+    expiries: Arc<RwLock<HashMap<String, NaiveDateTime>>>,
+}
+
+impl RevokedTokens {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, jti: &str) -> bool {
+        self.expiries
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .contains_key(jti)
+    }
+
+    pub fn insert(&self, jti: String, expires_at: NaiveDateTime) {
+        self.expiries
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(jti, expires_at);
+    }
+
+    /// Merges in the revocations stored in the master database and forgets tokens that have
+    /// expired. Entries are never dropped early, so a revocation made here while the query ran
+    /// isn't lost.
+    pub async fn refresh(&self, master_service: &MasterService) -> Result<(), sea_orm::DbErr> {
+        let stored = master_service.revoked_tokens().await?;
+        let now = Utc::now().naive_utc();
+
+        let mut expiries = self.expiries.write().unwrap_or_else(|e| e.into_inner());
+        expiries.extend(stored);
+        expiries.retain(|_, expires_at| *expires_at > now);
+        Ok(())
+    }
+}
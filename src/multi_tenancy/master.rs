@@ -1,86 +1,759 @@
-use sea_orm::{DatabaseConnection, Statement, DatabaseBackend, ConnectionTrait};
-use chrono::{Utc, NaiveDateTime};
+use std::collections::HashSet;
+use sea_orm::{
+    DatabaseConnection, Statement, DatabaseBackend, ConnectionTrait, TransactionTrait,
+    ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set,
+    sea_query::{Expr, Func},
+};
+use chrono::{Duration, Utc, NaiveDateTime};
 use uuid::Uuid;
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use password_hash::{rand_core::OsRng, SaltString};
-use crate::types::shared::{CreateTenantRequest, TenantResponse, CreateUserRequest, UserResponse, LoginRequest, LoginResponse};
-use crate::middlewares::create_jwt_token;
+use rand::{Rng, distributions::Alphanumeric};
+use sha2::{Digest, Sha256};
+use crate::types::shared::{
+    CreateTenantRequest, TenantResponse, CreateUserRequest, UserResponse, LoginRequest, LoginResponse,
+    AuthenticatedClient, GrantStatus, PermissionGrantResult, TenantSummary, AuditEntry, AuditLogEntry,
+};
+use crate::middlewares::{JwtKeys, create_jwt_token};
+use crate::entities::tenant::users as tenant_users;
+use crate::entities::master::{idempotency_keys, password_resets, revoked_tokens, tenants, users};
+use super::TenantConnectionManager;
+use tracing::{error, info, warn};
+use crate::error::{ApiError, is_unique_violation};
+use crate::types::users::display_name;
+use crate::pagination::Paginated;
+use crate::permissions;
+use crate::validation::{PasswordPolicy, validate_password_strength};
+
+/// Default upper bound on how many permissions a user or API client may hold.
+pub const DEFAULT_MAX_PERMISSIONS: usize = 64;
+
+/// Permissions granted to newly registered users.
+const DEFAULT_USER_PERMISSIONS: &[&str] = &[permissions::USERS_READ, permissions::USERS_WRITE];
 
 pub struct MasterService {
     db: DatabaseConnection,
+    max_permissions: usize,
 }
 
 impl MasterService {
     pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+        Self {
+            db,
+            max_permissions: DEFAULT_MAX_PERMISSIONS,
+        }
+    }
+    
+    /// Overrides the cap on permissions per user or API client. Every permission ends up in
+    /// issued JWTs, so this bounds token size.
+    pub fn with_max_permissions(mut self, max_permissions: usize) -> Self {
+        self.max_permissions = max_permissions;
+        self
+    }
+    
+    fn check_permissions_count(&self, count: usize) -> Result<(), ApiError> {
+        if count > self.max_permissions {
+            return Err(ApiError::UnprocessableEntity(format!(
+                "At most {} permissions may be assigned, got {}",
+                self.max_permissions, count
+            )));
+        }
+        Ok(())
+    }
+    
+    /// Inserts an active tenant with the given id, which the caller has already validated
+    /// with `validate_tenant_id`.
+    pub async fn create_tenant(&self, tenant_id: String, tenant_data: CreateTenantRequest) -> Result<TenantResponse, sea_orm::DbErr> {
+        // Timestamps are set by the entity's `before_save`
+        let tenant = tenants::ActiveModel {
+            id: Set(tenant_id),
+            name: Set(tenant_data.name),
+            status: Set("active".to_string()),
+            region: Set(tenant_data.region),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await?;
+        
+        Ok(tenant_response(tenant))
+    }
+    
+    /// Removes a tenant row outright. Only meant for undoing `create_tenant` when the tenant's
+    /// database could not be set up, before anything else refers to the tenant.
+    pub async fn discard_tenant(&self, tenant_id: &str) -> Result<(), sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "DELETE FROM tenants WHERE id = $1",
+            vec![tenant_id.into()]
+        );
+        
+        self.db.execute(stmt).await?;
+        Ok(())
+    }
+    
+    pub async fn get_tenant(&self, tenant_id: &str) -> Result<Option<TenantResponse>, sea_orm::DbErr> {
+        let tenant = tenants::Entity::find_by_id(tenant_id).one(&self.db).await?;
+        Ok(tenant.map(tenant_response))
+    }
+    
+    pub async fn create_user(
+        &self,
+        user_data: CreateUserRequest,
+        tenant_id: &str,
+        password_policy: &PasswordPolicy,
+    ) -> Result<UserResponse, ApiError> {
+        validate_password_strength(&user_data.password, password_policy)?;
+        let permissions = match &user_data.permissions {
+            Some(permissions) => self.known_permissions(permissions).await?,
+            None => DEFAULT_USER_PERMISSIONS.iter().map(|p| p.to_string()).collect(),
+        };
+        
+        let user = users::ActiveModel {
+            id: Set(Uuid::new_v4().to_string()),
+            tenant_id: Set(tenant_id.to_string()),
+            email: Set(user_data.email.trim().to_lowercase()),
+            password_hash: Set(hash_password(&user_data.password)?),
+            permissions: Set(serde_json::json!(permissions)),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await;
+        
+        let user = match user {
+            Ok(user) => user,
+            // idx_users_tenant_id_lower_email also catches addresses differing only in case
+            Err(e) if is_unique_violation(&e) => {
+                return Err(ApiError::Conflict("A user with this email already exists".to_string()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        
+        Ok(UserResponse {
+            id: user.id,
+            email: user.email,
+            display_name: display_name(&user_data.first_name, &user_data.last_name),
+            first_name: user_data.first_name,
+            last_name: user_data.last_name,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        })
+    }
+    
+    /// Replaces a user's password once `current_password` has been verified.
+    ///
+    /// Returns 401 if the current password is wrong or the user doesn't exist in the tenant, and
+    /// 400 if the new password fails the policy. Tokens issued earlier stay valid until expiry.
+    pub async fn change_password(
+        &self,
+        user_id: &str,
+        tenant_id: &str,
+        current_password: &str,
+        new_password: &str,
+        password_policy: &PasswordPolicy,
+    ) -> Result<(), ApiError> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT password_hash FROM users WHERE id = $1 AND tenant_id = $2",
+            vec![user_id.into(), tenant_id.into()]
+        );
+        
+        let password_hash: String = match self.db.query_one(stmt).await? {
+            Some(row) => row.try_get("", "password_hash")?,
+            None => return Err(ApiError::Unauthorized),
+        };
+        
+        if !verify_password(current_password, &password_hash)? {
+            return Err(ApiError::Unauthorized);
+        }
+        
+        validate_password_strength(new_password, password_policy)?;
+        
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE users SET password_hash = $1, updated_at = $2 WHERE id = $3 AND tenant_id = $4",
+            vec![
+                hash_password(new_password)?.into(),
+                Utc::now().naive_utc().into(),
+                user_id.into(),
+                tenant_id.into()
+            ]
+        );
+        
+        self.db.execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Issues a single-use password reset token for the user with `email` in the tenant, valid
+    /// for `ttl`. Returns `None` when there is no such user; callers must not reveal which.
+    pub async fn create_password_reset(&self, email: &str, tenant_id: &str, ttl: Duration) -> Result<Option<String>, sea_orm::DbErr> {
+        let user = users::Entity::find()
+            .filter(Expr::expr(Func::lower(Expr::col(users::Column::Email))).eq(Func::lower(Expr::val(email))))
+            .filter(users::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?;
+        
+        let Some(user) = user else {
+            return Ok(None);
+        };
+        
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(PASSWORD_RESET_TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+        let now = Utc::now().naive_utc();
+        
+        password_resets::ActiveModel {
+            token_hash: Set(reset_token_hash(&token)),
+            user_id: Set(user.id),
+            tenant_id: Set(user.tenant_id),
+            expires_at: Set(now + ttl),
+            used: Set(false),
+            created_at: Set(now),
+        }
+        .insert(&self.db)
+        .await?;
+        
+        Ok(Some(token))
     }
     
-    pub async fn create_tenant(&self, tenant_data: CreateTenantRequest) -> Result<TenantResponse, sea_orm::DbErr> {
-        let tenant_id = tenant_data.id;
-        let name = tenant_data.name;
+    /// Sets a new password with a reset token and returns the `(user_id, tenant_id)` it belonged
+    /// to. The token is consumed, along with any other outstanding tokens of the same user.
+    ///
+    /// Unknown, expired and already used tokens are all rejected with the same `400`.
+    pub async fn reset_password(
+        &self,
+        token: &str,
+        new_password: &str,
+        password_policy: &PasswordPolicy,
+    ) -> Result<(String, String), ApiError> {
+        // Checked first, so a weak password doesn't use up the token
+        validate_password_strength(new_password, password_policy)?;
+        
         let now = Utc::now().naive_utc();
+        let txn = self.db.begin().await?;
+        
+        // Claiming the token in the UPDATE itself keeps two concurrent resets from both using it
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE password_resets SET used = TRUE WHERE token_hash = $1 AND used = FALSE AND expires_at > $2 RETURNING user_id, tenant_id",
+            vec![reset_token_hash(token).into(), now.into()]
+        );
+        
+        let (user_id, tenant_id): (String, String) = match txn.query_one(stmt).await? {
+            Some(row) => (row.try_get("", "user_id")?, row.try_get("", "tenant_id")?),
+            None => return Err(ApiError::BadRequest("Invalid or expired reset token".to_string())),
+        };
         
-        // Insert tenant into master database
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "INSERT INTO tenants (id, name, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+            "UPDATE users SET password_hash = $1, updated_at = $2 WHERE id = $3 AND tenant_id = $4",
             vec![
-                tenant_id.clone().into(),
-                name.clone().into(),
-                "active".into(),
+                hash_password(new_password)?.into(),
                 now.into(),
-                now.into()
+                user_id.clone().into(),
+                tenant_id.clone().into()
             ]
         );
+        txn.execute(stmt).await?;
         
-        self.db.execute(stmt).await?;
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE password_resets SET used = TRUE WHERE user_id = $1 AND used = FALSE",
+            vec![user_id.clone().into()]
+        );
+        txn.execute(stmt).await?;
         
-        Ok(TenantResponse {
-            id: tenant_id,
-            name,
-            status: "active".to_string(),
-            created_at: now,
-            updated_at: now,
-        })
+        txn.commit().await?;
+        Ok((user_id, tenant_id))
     }
     
-    pub async fn get_tenant(&self, tenant_id: &str) -> Result<Option<TenantResponse>, sea_orm::DbErr> {
+    /// Sets a tenant's status and returns the updated tenant. Returns 404 for unknown tenants
+    /// and 409 for deleted ones, which can't be brought back.
+    pub async fn set_tenant_status(&self, tenant_id: &str, status: &str) -> Result<TenantResponse, ApiError> {
+        let tenant = self
+            .get_tenant(tenant_id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound(format!("Tenant {} not found", tenant_id)))?;
+        let deleted = || ApiError::Conflict("Tenant is deleted".to_string());
+        if tenant.status == TENANT_STATUS_DELETED {
+            return Err(deleted());
+        }
+        
+        let now = Utc::now().naive_utc();
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "SELECT id, name, status, created_at, updated_at FROM tenants WHERE id = $1",
-            vec![tenant_id.into()]
+            "UPDATE tenants SET status = $1, updated_at = $2 WHERE id = $3 AND status <> $4",
+            vec![status.into(), now.into(), tenant_id.into(), TENANT_STATUS_DELETED.into()]
+        );
+        // Deleted between the lookup and the update
+        if self.db.execute(stmt).await?.rows_affected() == 0 {
+            return Err(deleted());
+        }
+        
+        Ok(TenantResponse { status: status.to_string(), updated_at: now, ..tenant })
+    }
+    
+    /// Marks a suspended tenant as `deleted`. Active tenants must be suspended first, so a
+    /// single mistaken call can't delete a tenant that is in use.
+    pub async fn delete_tenant(&self, tenant_id: &str) -> Result<(), ApiError> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE tenants SET status = $1, updated_at = $2 WHERE id = $3 AND status = $4",
+            vec![
+                TENANT_STATUS_DELETED.into(),
+                Utc::now().naive_utc().into(),
+                tenant_id.into(),
+                TENANT_STATUS_SUSPENDED.into()
+            ]
         );
         
-        let result = self.db.query_one(stmt).await?;
+        if self.db.execute(stmt).await?.rows_affected() == 0 {
+            // Nothing matched: report why using the tenant's current status
+            let tenant = self
+                .get_tenant(tenant_id)
+                .await?
+                .ok_or_else(|| ApiError::NotFound(format!("Tenant {} not found", tenant_id)))?;
+            ensure_tenant_deletable(&tenant.status)?;
+        }
         
-        if let Some(row) = result {
-            Ok(Some(TenantResponse {
-                id: row.try_get::<String>("", "id").map_err(|_| sea_orm::DbErr::Custom("Failed to get id".to_string()))?,
-                name: row.try_get::<String>("", "name").map_err(|_| sea_orm::DbErr::Custom("Failed to get name".to_string()))?,
-                status: row.try_get::<String>("", "status").map_err(|_| sea_orm::DbErr::Custom("Failed to get status".to_string()))?,
-                created_at: row.try_get::<NaiveDateTime>("", "created_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get created_at".to_string()))?,
-                updated_at: row.try_get::<NaiveDateTime>("", "updated_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get updated_at".to_string()))?,
+        Ok(())
+    }
+    
+    /// Lists tenants one page at a time (1-based), optionally filtered by status, together with
+    /// the total number of matching tenants.
+    pub async fn list_tenants(&self, page: u64, page_size: u64, status_filter: Option<&str>) -> Result<(Vec<TenantResponse>, u64), sea_orm::DbErr> {
+        let offset = page.saturating_sub(1).saturating_mul(page_size);
+        
+        let count_stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT COUNT(*) AS total FROM tenants WHERE ($1::text IS NULL OR status = $1)",
+            vec![status_filter.into()]
+        );
+        let total = match self.db.query_one(count_stmt).await? {
+            Some(row) => row.try_get::<i64>("", "total")? as u64,
+            None => 0,
+        };
+        
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT id, name, status, region, created_at, updated_at FROM tenants \
+             WHERE ($1::text IS NULL OR status = $1) \
+             ORDER BY created_at, id LIMIT $2 OFFSET $3",
+            vec![status_filter.into(), (page_size as i64).into(), (offset as i64).into()]
+        );
+        
+        let tenants = self.db.query_all(stmt).await?
+            .iter()
+            .map(|row| Ok(TenantResponse {
+                id: row.try_get("", "id")?,
+                name: row.try_get("", "name")?,
+                status: row.try_get("", "status")?,
+                region: row.try_get("", "region")?,
+                created_at: row.try_get("", "created_at")?,
+                updated_at: row.try_get("", "updated_at")?,
             }))
-        } else {
-            Ok(None)
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()?;
+        
+        Ok((tenants, total))
+    }
+    
+    /// Deduplicates a requested permission set, checking it against the `permissions` catalog
+    /// and the configured maximum (422 if either is violated).
+    async fn known_permissions(&self, permissions: &[String]) -> Result<Vec<String>, ApiError> {
+        let mut unique = Vec::with_capacity(permissions.len());
+        for permission in permissions {
+            if !unique.contains(permission) {
+                unique.push(permission.clone());
+            }
         }
+        self.check_permissions_count(unique.len())?;
+        
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT name FROM permissions WHERE name IN (SELECT jsonb_array_elements_text($1::jsonb))",
+            vec![serde_json::json!(unique).into()]
+        );
+        let known = self.db.query_all(stmt).await?
+            .iter()
+            .map(|row| row.try_get::<String>("", "name"))
+            .collect::<Result<HashSet<_>, _>>()?;
+        
+        if let Some(unknown) = unique.iter().find(|permission| !known.contains(*permission)) {
+            return Err(ApiError::UnprocessableEntity(format!("Unknown permission '{}'", unknown)));
+        }
+        
+        Ok(unique)
+    }
+    
+    /// Replaces a user's permissions and returns the stored set, with duplicates removed.
+    ///
+    /// Returns 404 if the user doesn't exist in the tenant and 422 if a permission isn't in the
+    /// catalog or the list exceeds the configured maximum.
+    pub async fn set_user_permissions(&self, user_id: &str, tenant_id: &str, permissions: &[String]) -> Result<Vec<String>, ApiError> {
+        let permissions = self.known_permissions(permissions).await?;
+        
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE users SET permissions = $1, updated_at = $2 WHERE id = $3 AND tenant_id = $4",
+            vec![
+                serde_json::json!(permissions).into(),
+                Utc::now().naive_utc().into(),
+                user_id.into(),
+                tenant_id.into()
+            ]
+        );
+        
+        if self.db.execute(stmt).await?.rows_affected() == 0 {
+            return Err(ApiError::NotFound(format!("User {} not found", user_id)));
+        }
+        
+        Ok(permissions)
     }
     
-    pub async fn create_user(&self, user_data: CreateUserRequest, tenant_id: &str) -> Result<UserResponse, sea_orm::DbErr> {
-        let user_id = Uuid::new_v4().to_string();
-        let password_hash = hash_password(&user_data.password)?;
+    /// Inserts the built-in permissions into the `permissions` catalog, leaving existing rows
+    /// untouched, and returns how many were added. Safe to run on every startup.
+    pub async fn seed_default_permissions(&self) -> Result<u64, sea_orm::DbErr> {
+        let mut inserted = 0;
+        
+        for (name, description) in permissions::DEFAULT_PERMISSIONS {
+            let stmt = Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "INSERT INTO permissions (id, name, description) VALUES ($1, $2, $3) ON CONFLICT (name) DO NOTHING",
+                vec![Uuid::new_v4().to_string().into(), (*name).into(), (*description).into()]
+            );
+            inserted += self.db.execute(stmt).await?.rows_affected();
+        }
+        
+        Ok(inserted)
+    }
+    
+    /// Adds `permission` to each listed user of the tenant in a single transaction.
+    ///
+    /// The permission must exist in the `permissions` catalog (422 otherwise). Users that don't
+    /// exist, already hold it, or are at the permission cap are reported individually rather
+    /// than failing the batch.
+    pub async fn grant_permission(&self, tenant_id: &str, user_ids: &[String], permission: &str) -> Result<Vec<PermissionGrantResult>, ApiError> {
+        let catalog_stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT 1 FROM permissions WHERE name = $1",
+            vec![permission.into()]
+        );
+        if self.db.query_one(catalog_stmt).await?.is_none() {
+            return Err(ApiError::UnprocessableEntity(format!("Unknown permission '{}'", permission)));
+        }
+        
+        let txn = self.db.begin().await?;
+        let mut results = Vec::with_capacity(user_ids.len());
+        
+        for user_id in user_ids {
+            let select_stmt = Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT permissions FROM users WHERE id = $1 AND tenant_id = $2 FOR UPDATE",
+                vec![user_id.clone().into(), tenant_id.into()]
+            );
+            
+            let status = match txn.query_one(select_stmt).await? {
+                None => GrantStatus::NotFound,
+                Some(row) => {
+                    let permissions_value: serde_json::Value = row.try_get("", "permissions")?;
+                    let mut permissions: Vec<String> = serde_json::from_value(permissions_value)
+                        .map_err(|_| sea_orm::DbErr::Custom("Failed to parse permissions".to_string()))?;
+                    
+                    if permissions.iter().any(|p| p == permission) {
+                        GrantStatus::AlreadyGranted
+                    } else if permissions.len() >= self.max_permissions {
+                        GrantStatus::LimitExceeded
+                    } else {
+                        permissions.push(permission.to_string());
+                        let update_stmt = Statement::from_sql_and_values(
+                            DatabaseBackend::Postgres,
+                            "UPDATE users SET permissions = $1, updated_at = $2 WHERE id = $3 AND tenant_id = $4",
+                            vec![
+                                serde_json::json!(permissions).into(),
+                                Utc::now().naive_utc().into(),
+                                user_id.clone().into(),
+                                tenant_id.into()
+                            ]
+                        );
+                        txn.execute(update_stmt).await?;
+                        GrantStatus::Granted
+                    }
+                }
+            };
+            
+            results.push(PermissionGrantResult { user_id: user_id.clone(), status });
+        }
+        
+        txn.commit().await?;
+        
+        Ok(results)
+    }
+    
+    /// Checks whether `email` is already registered in the tenant, ignoring case.
+    pub async fn email_exists(&self, email: &str, tenant_id: &str) -> Result<bool, sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT 1 FROM users WHERE LOWER(email) = LOWER($1) AND tenant_id = $2 LIMIT 1",
+            vec![email.into(), tenant_id.into()]
+        );
+        
+        Ok(self.db.query_one(stmt).await?.is_some())
+    }
+    
+    /// Active tenants in which `email` has an account (case-insensitive), ordered by name.
+    pub async fn tenants_for_email(&self, email: &str) -> Result<Vec<TenantSummary>, sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT DISTINCT t.id, t.name FROM tenants t JOIN users u ON u.tenant_id = t.id \
+             WHERE LOWER(u.email) = LOWER($1) AND t.status = $2 \
+             ORDER BY t.name, t.id",
+            vec![email.into(), "active".into()]
+        );
+        
+        self.db.query_all(stmt).await?
+            .iter()
+            .map(|row| Ok(TenantSummary {
+                id: row.try_get("", "id")?,
+                name: row.try_get("", "name")?,
+            }))
+            .collect()
+    }
+    
+    /// Appends an entry to the audit log.
+    pub async fn record_audit(&self, entry: AuditEntry) -> Result<(), sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "INSERT INTO audit_log (id, tenant_id, actor_user_id, action, target_id, metadata, created_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            vec![
+                Uuid::new_v4().to_string().into(),
+                entry.tenant_id.into(),
+                entry.actor_user_id.into(),
+                entry.action.into(),
+                entry.target_id.into(),
+                entry.metadata.into(),
+                Utc::now().naive_utc().into()
+            ]
+        );
+        
+        self.db.execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Like `record_audit`, but logs a failure instead of returning it: by the time an action
+    /// is audited it has already happened, so the caller's request should still succeed.
+    pub async fn try_record_audit(&self, entry: AuditEntry) {
+        let (action, target_id) = (entry.action, entry.target_id.clone());
+        if let Err(e) = self.record_audit(entry).await {
+            error!(action = action, target_id = %target_id, error = %e, "Failed to write audit log entry");
+        }
+    }
+    
+    /// Lists a tenant's audit entries newest first, one page (1-based) at a time, optionally
+    /// restricted to one action.
+    pub async fn list_audit(&self, tenant_id: &str, action: Option<&str>, page: u32, page_size: u32) -> Result<Paginated<AuditLogEntry>, sea_orm::DbErr> {
+        let offset = (page.saturating_sub(1) as u64).saturating_mul(page_size as u64);
+        
+        let count_stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT COUNT(*) AS total FROM audit_log WHERE tenant_id = $1 AND ($2::text IS NULL OR action = $2)",
+            vec![tenant_id.into(), action.into()]
+        );
+        let total_count = match self.db.query_one(count_stmt).await? {
+            Some(row) => row.try_get::<i64>("", "total")? as u64,
+            None => 0,
+        };
+        
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT id, tenant_id, actor_user_id, action, target_id, metadata, created_at FROM audit_log \
+             WHERE tenant_id = $1 AND ($2::text IS NULL OR action = $2) \
+             ORDER BY created_at DESC, id DESC LIMIT $3 OFFSET $4",
+            vec![tenant_id.into(), action.into(), (page_size as i64).into(), (offset as i64).into()]
+        );
+        
+        let items = self.db.query_all(stmt).await?
+            .iter()
+            .map(|row| Ok(AuditLogEntry {
+                id: row.try_get("", "id")?,
+                tenant_id: row.try_get("", "tenant_id")?,
+                actor_user_id: row.try_get("", "actor_user_id")?,
+                action: row.try_get("", "action")?,
+                target_id: row.try_get("", "target_id")?,
+                metadata: row.try_get("", "metadata")?,
+                created_at: row.try_get("", "created_at")?,
+            }))
+            .collect::<Result<Vec<_>, sea_orm::DbErr>>()?;
+        
+        Ok(Paginated::new(items, total_count, page, page_size))
+    }
+    
+    /// Deletes a tenant's audit entries created before `older_than`, `batch_size` rows per
+    /// statement so no single delete holds locks for long. Returns how many were removed.
+    ///
+    /// Each batch commits on its own, so dropping the future stops the purge between batches
+    /// without losing the work already done.
+    pub async fn purge_audit_log(&self, tenant_id: &str, older_than: NaiveDateTime, batch_size: u64) -> Result<u64, sea_orm::DbErr> {
+        let mut purged = 0;
+        loop {
+            let stmt = Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "DELETE FROM audit_log WHERE id IN \
+                 (SELECT id FROM audit_log WHERE tenant_id = $1 AND created_at < $2 LIMIT $3)",
+                vec![tenant_id.into(), older_than.into(), (batch_size as i64).into()]
+            );
+            
+            let deleted = self.db.execute(stmt).await?.rows_affected();
+            purged += deleted;
+            if deleted < batch_size {
+                return Ok(purged);
+            }
+        }
+    }
+    
+    /// Purges audit entries older than `older_than` for every tenant, one tenant at a time, and
+    /// returns the total removed.
+    pub async fn purge_audit_log_all(&self, older_than: NaiveDateTime, batch_size: u64) -> Result<u64, sea_orm::DbErr> {
+        let mut total = 0;
+        for tenant_id in self.audited_tenant_ids().await? {
+            let purged = self.purge_audit_log(&tenant_id, older_than, batch_size).await?;
+            if purged > 0 {
+                info!(tenant_id = %tenant_id, purged, "Purged expired audit entries");
+            }
+            total += purged;
+        }
+        Ok(total)
+    }
+    
+    /// Tenants with at least one audit entry, including tenants that have since been deleted.
+    async fn audited_tenant_ids(&self) -> Result<Vec<String>, sea_orm::DbErr> {
+        let stmt = Statement::from_string(
+            DatabaseBackend::Postgres,
+            "SELECT DISTINCT tenant_id FROM audit_log ORDER BY tenant_id",
+        );
+        
+        self.db.query_all(stmt).await?
+            .iter()
+            .map(|row| row.try_get("", "tenant_id"))
+            .collect()
+    }
+    
+    /// Claims an idempotency key for a request with the given hash. Returns false if the key is
+    /// already taken, unless its holder never finished and started before `abandoned_before`,
+    /// in which case the claim is taken over.
+    pub async fn claim_idempotency_key(&self, scope: &str, key: &str, request_hash: &str, abandoned_before: NaiveDateTime) -> Result<bool, sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "INSERT INTO idempotency_keys (scope, key, request_hash, created_at) VALUES ($1, $2, $3, $4) \
+             ON CONFLICT (scope, key) DO UPDATE SET request_hash = EXCLUDED.request_hash, created_at = EXCLUDED.created_at \
+             WHERE idempotency_keys.status_code IS NULL AND idempotency_keys.created_at < $5",
+            vec![
+                scope.into(),
+                key.into(),
+                request_hash.into(),
+                Utc::now().naive_utc().into(),
+                abandoned_before.into()
+            ]
+        );
+        
+        Ok(self.db.execute(stmt).await?.rows_affected() == 1)
+    }
+    
+    pub async fn idempotency_record(&self, scope: &str, key: &str) -> Result<Option<idempotency_keys::Model>, sea_orm::DbErr> {
+        idempotency_keys::Entity::find_by_id((scope.to_string(), key.to_string()))
+            .one(&self.db)
+            .await
+    }
+    
+    /// Stores the response for a claimed key, so repeats of the request replay it.
+    pub async fn complete_idempotency_key(&self, scope: &str, key: &str, status_code: u16, response_body: &serde_json::Value) -> Result<(), sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "UPDATE idempotency_keys SET status_code = $3, response_body = $4 WHERE scope = $1 AND key = $2",
+            vec![scope.into(), key.into(), (status_code as i16).into(), response_body.clone().into()]
+        );
+        
+        self.db.execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Gives up a claimed key whose request failed, so the client can retry with it.
+    pub async fn release_idempotency_key(&self, scope: &str, key: &str) -> Result<(), sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "DELETE FROM idempotency_keys WHERE scope = $1 AND key = $2 AND status_code IS NULL",
+            vec![scope.into(), key.into()]
+        );
+        
+        self.db.execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Records a token id as revoked until `expires_at`, when the token would stop working anyway.
+    /// Revoking the same token twice is a no-op.
+    pub async fn revoke_token(&self, jti: &str, tenant_id: &str, expires_at: NaiveDateTime) -> Result<(), sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "INSERT INTO revoked_tokens (jti, tenant_id, expires_at, revoked_at) VALUES ($1, $2, $3, $4) ON CONFLICT (jti) DO NOTHING",
+            vec![jti.into(), tenant_id.into(), expires_at.into(), Utc::now().naive_utc().into()]
+        );
+        
+        self.db.execute(stmt).await?;
+        Ok(())
+    }
+    
+    /// Ids and expiry times of revoked tokens that haven't expired yet.
+    pub async fn revoked_tokens(&self) -> Result<Vec<(String, NaiveDateTime)>, sea_orm::DbErr> {
+        let rows = revoked_tokens::Entity::find()
+            .filter(revoked_tokens::Column::ExpiresAt.gt(Utc::now().naive_utc()))
+            .all(&self.db)
+            .await?;
+        
+        Ok(rows.into_iter().map(|row| (row.jti, row.expires_at)).collect())
+    }
+    
+    /// Deletes revocations of tokens that have since expired, returning how many were removed.
+    pub async fn purge_expired_revoked_tokens(&self) -> Result<u64, sea_orm::DbErr> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "DELETE FROM revoked_tokens WHERE expires_at <= $1",
+            vec![Utc::now().naive_utc().into()]
+        );
+        
+        Ok(self.db.execute(stmt).await?.rows_affected())
+    }
+    
+    /// Registers a machine client for a tenant and returns its `(client_id, client_secret)`
+    /// along with the stored scopes, with duplicates removed.
+    ///
+    /// Every scope must be in the `permissions` catalog, and there may be no more than the
+    /// configured maximum (400 otherwise). Only a hash of the secret is stored, so the returned
+    /// secret cannot be recovered later.
+    pub async fn create_api_client(&self, tenant_id: &str, name: &str, scopes: &[String]) -> Result<(String, String, Vec<String>), ApiError> {
+        let scopes = self.known_permissions(scopes).await.map_err(|e| match e {
+            ApiError::UnprocessableEntity(message) => ApiError::BadRequest(message),
+            other => other,
+        })?;
+        
+        let client_id = Uuid::new_v4().to_string();
+        let client_secret: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(API_CLIENT_SECRET_LENGTH)
+            .map(char::from)
+            .collect();
         let now = Utc::now().naive_utc();
         
-        // Insert user into master database
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "INSERT INTO users (id, tenant_id, email, password_hash, permissions, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            "INSERT INTO api_clients (id, tenant_id, name, secret_hash, scopes, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
             vec![
-                user_id.clone().into(),
+                client_id.clone().into(),
                 tenant_id.into(),
-                user_data.email.clone().into(),
-                password_hash.into(),
-                serde_json::json!(["users:read", "users:write"]).into(),
+                name.into(),
+                hash_password(&client_secret)?.into(),
+                serde_json::json!(scopes).into(),
+                "active".into(),
                 now.into(),
                 now.into()
             ]
@@ -88,54 +761,97 @@ impl MasterService {
         
         self.db.execute(stmt).await?;
         
-        Ok(UserResponse {
-            id: user_id,
-            email: user_data.email,
-            first_name: user_data.first_name,
-            last_name: user_data.last_name,
-            created_at: now,
-            updated_at: now,
-        })
+        Ok((client_id, client_secret, scopes))
     }
     
-    pub async fn authenticate_user(&self, login_data: LoginRequest, tenant_id: &str) -> Result<Option<LoginResponse>, sea_orm::DbErr> {
+    /// Verifies client credentials, returning the client's tenant and scopes when they match
+    /// an active client of an active tenant.
+    pub async fn authenticate_client(&self, client_id: &str, client_secret: &str) -> Result<Option<AuthenticatedClient>, sea_orm::DbErr> {
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "SELECT id, email, password_hash, permissions FROM users WHERE email = $1 AND tenant_id = $2",
-            vec![login_data.email.clone().into(), tenant_id.into()]
+            "SELECT c.tenant_id, c.secret_hash, c.scopes FROM api_clients c \
+             JOIN tenants t ON t.id = c.tenant_id \
+             WHERE c.id = $1 AND c.status = 'active' AND t.status = 'active'",
+            vec![client_id.into()]
         );
         
-        let result = self.db.query_one(stmt).await?;
+        let Some(row) = self.db.query_one(stmt).await? else {
+            return Ok(None);
+        };
+        
+        let secret_hash: String = row.try_get("", "secret_hash")?;
+        if !verify_password(client_secret, &secret_hash)? {
+            return Ok(None);
+        }
+        
+        let scopes_value: serde_json::Value = row.try_get("", "scopes")?;
+        let scopes: Vec<String> = serde_json::from_value(scopes_value)
+            .map_err(|_| sea_orm::DbErr::Custom("Failed to parse client scopes".to_string()))?;
+        
+        Ok(Some(AuthenticatedClient {
+            client_id: client_id.to_string(),
+            tenant_id: row.try_get("", "tenant_id")?,
+            scopes,
+        }))
+    }
+    
+    /// Verifies the credentials and issues a token, returning `None` if they don't match.
+    ///
+    /// The returned user carries the name and timestamps of the tenant's profile with the same
+    /// email. If the profile is missing or the tenant database can't be reached, login still
+    /// succeeds with empty names and the master account's timestamps, and a warning is logged.
+    pub async fn authenticate_user(
+        &self,
+        login_data: LoginRequest,
+        tenant_id: &str,
+        tenant_manager: &TenantConnectionManager,
+        jwt_keys: &JwtKeys,
+        jwt_expiration: u64,
+    ) -> Result<Option<LoginResponse>, sea_orm::DbErr> {
+        let result = users::Entity::find()
+            // Matches idx_users_tenant_id_lower_email, so accounts stored with mixed case stay reachable
+            .filter(Expr::expr(Func::lower(Expr::col(users::Column::Email))).eq(Func::lower(Expr::val(login_data.email.as_str()))))
+            .filter(users::Column::TenantId.eq(tenant_id))
+            .one(&self.db)
+            .await?;
         
         if let Some(row) = result {
-            let user_id: String = row.try_get::<String>("", "id").map_err(|_| sea_orm::DbErr::Custom("Failed to get user id".to_string()))?;
-            let email: String = row.try_get::<String>("", "email").map_err(|_| sea_orm::DbErr::Custom("Failed to get email".to_string()))?;
-            let password_hash: String = row.try_get::<String>("", "password_hash").map_err(|_| sea_orm::DbErr::Custom("Failed to get password_hash".to_string()))?;
-            let permissions_value: serde_json::Value = row.try_get::<serde_json::Value>("", "permissions").map_err(|_| sea_orm::DbErr::Custom("Failed to get permissions".to_string()))?;
-            
-            if verify_password(&login_data.password, &password_hash)? {
-                let permissions: Vec<String> = serde_json::from_value(permissions_value)
+            if verify_password(&login_data.password, &row.password_hash)? {
+                let permissions: Vec<String> = serde_json::from_value(row.permissions)
                     .map_err(|_| sea_orm::DbErr::Custom("Failed to parse permissions".to_string()))?;
+                let user_id = row.id;
+                let email = row.email;
                 
                 let token = create_jwt_token(
                     &user_id,
                     tenant_id,
                     &permissions,
-                    "your-secret-key", // This should come from config
-                    3600,
+                    jwt_keys,
+                    jwt_expiration,
                 ).map_err(|_| sea_orm::DbErr::Custom("Failed to create token".to_string()))?;
                 
-                Ok(Some(LoginResponse {
-                    token,
-                    user: UserResponse {
+                let user = match tenant_profile(tenant_manager, tenant_id, &email).await {
+                    Some(profile) => UserResponse {
                         id: user_id,
                         email,
-                        first_name: "".to_string(), // Would come from tenant database
-                        last_name: "".to_string(),
-                        created_at: Utc::now().naive_utc(), // Would come from tenant database
-                        updated_at: Utc::now().naive_utc(),
+                        display_name: display_name(&profile.first_name, &profile.last_name),
+                        first_name: profile.first_name,
+                        last_name: profile.last_name,
+                        created_at: profile.created_at,
+                        updated_at: profile.updated_at,
                     },
-                }))
+                    None => UserResponse {
+                        id: user_id,
+                        email,
+                        display_name: String::new(),
+                        first_name: String::new(),
+                        last_name: String::new(),
+                        created_at: row.created_at,
+                        updated_at: row.updated_at,
+                    },
+                };
+                
+                Ok(Some(LoginResponse { token, user }))
             } else {
                 Ok(None)
             }
@@ -145,6 +861,101 @@ impl MasterService {
     }
 }
 
+fn tenant_response(tenant: tenants::Model) -> TenantResponse {
+    TenantResponse {
+        id: tenant.id,
+        name: tenant.name,
+        status: tenant.status,
+        region: tenant.region,
+        created_at: tenant.created_at,
+        updated_at: tenant.updated_at,
+    }
+}
+
+/// Looks up the tenant's profile for `email`, logging a warning when there isn't one.
+async fn tenant_profile(
+    tenant_manager: &TenantConnectionManager,
+    tenant_id: &str,
+    email: &str,
+) -> Option<tenant_users::Model> {
+    let tenant_db = match tenant_manager.get_tenant_connection(tenant_id).await {
+        Ok(tenant_db) => tenant_db,
+        Err(e) => {
+            warn!(tenant_id = %tenant_id, error = %e, "Tenant database unavailable; login response has no profile");
+            return None;
+        }
+    };
+    
+    match tenant_users::Entity::find()
+        .filter(Expr::expr(Func::lower(Expr::col(tenant_users::Column::Email))).eq(email.to_lowercase()))
+        .one(&tenant_db)
+        .await
+    {
+        Ok(Some(profile)) => Some(profile),
+        Ok(None) => {
+            warn!(tenant_id = %tenant_id, "No tenant profile for authenticated user; login response has no profile");
+            None
+        }
+        Err(e) => {
+            warn!(tenant_id = %tenant_id, error = %e, "Failed to load tenant profile for login");
+            None
+        }
+    }
+}
+
+/// Status of a tenant whose users can sign in and whose database is served.
+pub const TENANT_STATUS_ACTIVE: &str = "active";
+
+/// Status a tenant must have before it can be deleted.
+pub const TENANT_STATUS_SUSPENDED: &str = "suspended";
+
+/// Status of a tenant whose database has been dropped.
+pub const TENANT_STATUS_DELETED: &str = "deleted";
+
+/// Rejects deleting a tenant unless it is suspended; `409 Conflict` otherwise.
+pub fn ensure_tenant_deletable(status: &str) -> Result<(), ApiError> {
+    match status {
+        TENANT_STATUS_SUSPENDED => Ok(()),
+        TENANT_STATUS_DELETED => Err(ApiError::Conflict("Tenant is already deleted".to_string())),
+        other => Err(ApiError::Conflict(format!(
+            "Tenant is {}; suspend it before deleting",
+            other
+        ))),
+    }
+}
+
+/// Accepts the statuses an admin may set directly, `400` otherwise. `deleted` is only reached
+/// by deleting the tenant.
+pub fn parse_tenant_status(status: &str) -> Result<&'static str, ApiError> {
+    match status {
+        TENANT_STATUS_ACTIVE => Ok(TENANT_STATUS_ACTIVE),
+        TENANT_STATUS_SUSPENDED => Ok(TENANT_STATUS_SUSPENDED),
+        other => Err(ApiError::BadRequest(format!(
+            "Unknown tenant status '{}'; expected active or suspended",
+            other
+        ))),
+    }
+}
+
+/// Entries created before this are outside an audit retention of `retention_days`.
+pub fn audit_retention_cutoff(now: NaiveDateTime, retention_days: u64) -> NaiveDateTime {
+    let days = i64::try_from(retention_days).unwrap_or(i64::MAX);
+    now.checked_sub_signed(Duration::try_days(days).unwrap_or(Duration::MAX))
+        .unwrap_or(NaiveDateTime::MIN)
+}
+
+/// Length of generated API client secrets (alphanumeric, ~238 bits of entropy).
+const API_CLIENT_SECRET_LENGTH: usize = 40;
+
+/// Length of generated password reset tokens (alphanumeric, ~238 bits of entropy).
+const PASSWORD_RESET_TOKEN_LENGTH: usize = 40;
+
+/// Reset tokens are random and long, so a fast unsalted hash is enough to keep stored ones
+/// unusable while still allowing lookup by token.
+fn reset_token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
 fn hash_password(password: &str) -> Result<String, sea_orm::DbErr> {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
@@ -160,4 +971,37 @@ fn verify_password(password: &str, hash: &str) -> Result<bool, sea_orm::DbErr> {
     Ok(Argon2::default()
         .verify_password(password.as_bytes(), &parsed_hash)
         .is_ok())
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_suspended_tenants_are_deletable() {
+        assert!(ensure_tenant_deletable(TENANT_STATUS_SUSPENDED).is_ok());
+        assert!(matches!(ensure_tenant_deletable(TENANT_STATUS_ACTIVE), Err(ApiError::Conflict(_))));
+        assert!(matches!(ensure_tenant_deletable(TENANT_STATUS_DELETED), Err(ApiError::Conflict(_))));
+    }
+
+    #[test]
+    fn admins_may_set_active_or_suspended() {
+        assert_eq!(parse_tenant_status("active").unwrap(), TENANT_STATUS_ACTIVE);
+        assert_eq!(parse_tenant_status("suspended").unwrap(), TENANT_STATUS_SUSPENDED);
+        for status in ["deleted", "Active", ""] {
+            assert!(matches!(parse_tenant_status(status), Err(ApiError::BadRequest(_))), "accepted {:?}", status);
+        }
+    }
+
+    #[test]
+    fn audit_retention_cutoff_subtracts_the_window() {
+        let now = NaiveDateTime::parse_from_str("2024-03-10 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        let cutoff = audit_retention_cutoff(now, 30);
+        assert_eq!(cutoff, NaiveDateTime::parse_from_str("2024-02-09 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap());
+    }
+
+    #[test]
+    fn audit_retention_cutoff_saturates_for_huge_windows() {
+        let now = NaiveDateTime::parse_from_str("2024-03-10 12:00:00", "%Y-%m-%d %H:%M:%S").unwrap();
+        assert_eq!(audit_retention_cutoff(now, u64::MAX), NaiveDateTime::MIN);
+    }
+}
@@ -2,7 +2,9 @@ pub mod tenant_manager;
 pub mod master;
 pub mod tenant;
 pub mod services;
+pub mod schema_drift;
 
-pub use tenant_manager::TenantConnectionManager;
-pub use master::MasterService;
-pub use tenant::TenantService; 
\ No newline at end of file
+pub use tenant_manager::{ConnectionStats, EvictionHook, MigrationOutcome, MigrationStatus, MisdirectedTenant, PRODUCT_NAME_INDEX, TenantConnectionManager, validate_tenant_id};
+pub use master::{DEFAULT_MAX_PERMISSIONS, MasterService, audit_retention_cutoff, ensure_tenant_deletable, parse_tenant_status};
+pub use tenant::{DEFAULT_MAX_UNPAGINATED_ROWS, TenantService};
+pub use schema_drift::SchemaDrift; 
\ No newline at end of file
@@ -0,0 +1,105 @@
+use std::collections::{BTreeMap, BTreeSet};
+use sea_orm::{
+    ConnectionTrait, DatabaseBackend, DatabaseConnection, DbErr, EntityTrait,
+    IdenStatic, Iterable, Statement,
+};
+use sea_orm_migration::MigratorTrait;
+use serde::Serialize;
+use tenant_migration::TenantMigrator;
+use crate::entities::tenant::{orders, products, users};
+
+/// Bookkeeping table maintained by sea-orm-migration; never part of the expected schema.
+const MIGRATIONS_TABLE: &str = "seaql_migrations";
+
+/// A single difference between a tenant database and the schema the migrations produce.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchemaDrift {
+    MissingTable { table: String },
+    UnexpectedTable { table: String },
+    MissingColumn { table: String, column: String },
+    UnexpectedColumn { table: String, column: String },
+    PendingMigration { name: String },
+}
+
+type Schema = BTreeMap<String, BTreeSet<String>>;
+
+fn entity_columns<E: EntityTrait>(schema: &mut Schema) {
+    schema.insert(
+        E::default().table_name().to_string(),
+        E::Column::iter().map(|column| column.as_str().to_string()).collect(),
+    );
+}
+
+/// Tables and columns a fully migrated tenant database is expected to have.
+///
+/// Derived from the tenant entities, which mirror the `TenantMigrator` migrations; a column
+/// added by a migration must also be added to its entity.
+fn expected_schema() -> Schema {
+    let mut schema = Schema::new();
+    entity_columns::<users::Entity>(&mut schema);
+    entity_columns::<products::Entity>(&mut schema);
+    entity_columns::<orders::Entity>(&mut schema);
+    schema
+}
+
+async fn actual_schema(db: &DatabaseConnection) -> Result<Schema, DbErr> {
+    let stmt = Statement::from_string(
+        DatabaseBackend::Postgres,
+        "SELECT table_name::text AS table_name, column_name::text AS column_name \
+         FROM information_schema.columns WHERE table_schema = current_schema()",
+    );
+
+    let mut schema = Schema::new();
+    for row in db.query_all(stmt).await? {
+        let table: String = row.try_get("", "table_name")?;
+        let column: String = row.try_get("", "column_name")?;
+        if table != MIGRATIONS_TABLE {
+            schema.entry(table).or_default().insert(column);
+        }
+    }
+
+    Ok(schema)
+}
+
+/// Compares two schemas and lists every table/column difference between them.
+fn diff_schemas(expected: &Schema, actual: &Schema) -> Vec<SchemaDrift> {
+    let mut drift = Vec::new();
+
+    for (table, expected_columns) in expected {
+        let Some(actual_columns) = actual.get(table) else {
+            drift.push(SchemaDrift::MissingTable { table: table.clone() });
+            continue;
+        };
+
+        drift.extend(expected_columns.difference(actual_columns).map(|column| {
+            SchemaDrift::MissingColumn { table: table.clone(), column: column.clone() }
+        }));
+        drift.extend(actual_columns.difference(expected_columns).map(|column| {
+            SchemaDrift::UnexpectedColumn { table: table.clone(), column: column.clone() }
+        }));
+    }
+
+    drift.extend(
+        actual
+            .keys()
+            .filter(|table| !expected.contains_key(*table))
+            .map(|table| SchemaDrift::UnexpectedTable { table: table.clone() }),
+    );
+
+    drift
+}
+
+/// Reports how a tenant database differs from the schema its migrations should produce,
+/// including any migrations that have not been applied yet. An empty list means no drift.
+pub async fn detect_schema_drift(db: &DatabaseConnection) -> Result<Vec<SchemaDrift>, DbErr> {
+    let mut drift: Vec<SchemaDrift> = TenantMigrator::get_pending_migrations(db)
+        .await?
+        .iter()
+        .map(|migration| SchemaDrift::PendingMigration { name: migration.name().to_string() })
+        .collect();
+
+    drift.extend(diff_schemas(&expected_schema(), &actual_schema(db).await?));
+
+    Ok(drift)
+}
@@ -1,20 +1,38 @@
-use sea_orm::{DatabaseConnection, Statement, DatabaseBackend, ConnectionTrait};
+use sea_orm::{DatabaseConnection, Statement, DatabaseBackend, ConnectionTrait, QueryResult};
 use chrono::{Utc, NaiveDateTime};
 use uuid::Uuid;
+use tracing::warn;
+use crate::pagination::{truncate_to_cap, unpaginated_limit};
 use crate::types::shared::{CreateUserRequest, UserResponse};
+use crate::types::users::display_name;
+
+/// Default upper bound on the number of rows returned by unpaginated listings.
+pub const DEFAULT_MAX_UNPAGINATED_ROWS: u64 = 10_000;
 
 pub struct TenantService {
     db: DatabaseConnection,
+    max_unpaginated_rows: u64,
 }
 
 impl TenantService {
     pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+        Self {
+            db,
+            max_unpaginated_rows: DEFAULT_MAX_UNPAGINATED_ROWS,
+        }
+    }
+    
+    /// Overrides the row cap applied by [`TenantService::get_users`].
+    pub fn with_max_unpaginated_rows(mut self, max_rows: u64) -> Self {
+        self.max_unpaginated_rows = max_rows;
+        self
     }
     
     pub async fn create_user(&self, user_data: CreateUserRequest) -> Result<UserResponse, sea_orm::DbErr> {
         let user_id = Uuid::new_v4().to_string();
         let now = Utc::now().naive_utc();
+        // Stored lowercased, as the users endpoints do, so lookups by email stay case-insensitive
+        let email = user_data.email.trim().to_lowercase();
         
         // Insert user into tenant database
         let stmt = Statement::from_sql_and_values(
@@ -22,7 +40,7 @@ impl TenantService {
             "INSERT INTO users (id, email, first_name, last_name, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6)",
             vec![
                 user_id.clone().into(),
-                user_data.email.clone().into(),
+                email.clone().into(),
                 user_data.first_name.clone().into(),
                 user_data.last_name.clone().into(),
                 now.into(),
@@ -34,7 +52,8 @@ impl TenantService {
         
         Ok(UserResponse {
             id: user_id,
-            email: user_data.email,
+            email,
+            display_name: display_name(&user_data.first_name, &user_data.last_name),
             first_name: user_data.first_name,
             last_name: user_data.last_name,
             created_at: now,
@@ -42,30 +61,57 @@ impl TenantService {
         })
     }
     
+    /// Returns every user in the tenant, up to the configured row cap.
+    ///
+    /// Results beyond the cap are dropped and a warning is logged. Prefer
+    /// [`TenantService::get_users_paginated`] for anything that may grow large.
     pub async fn get_users(&self) -> Result<Vec<UserResponse>, sea_orm::DbErr> {
+        let cap = self.max_unpaginated_rows;
+        
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "SELECT id, email, first_name, last_name, created_at, updated_at FROM users",
-            vec![]
+            "SELECT id, email, first_name, last_name, created_at, updated_at FROM users ORDER BY created_at, id LIMIT $1",
+            vec![(unpaginated_limit(cap) as i64).into()]
         );
         
         let result = self.db.query_all(stmt).await?;
         
-        let mut users = Vec::new();
-        for row in result {
-            users.push(UserResponse {
-                id: row.try_get::<String>("", "id").map_err(|_| sea_orm::DbErr::Custom("Failed to get id".to_string()))?,
-                email: row.try_get::<String>("", "email").map_err(|_| sea_orm::DbErr::Custom("Failed to get email".to_string()))?,
-                first_name: row.try_get::<String>("", "first_name").map_err(|_| sea_orm::DbErr::Custom("Failed to get first_name".to_string()))?,
-                last_name: row.try_get::<String>("", "last_name").map_err(|_| sea_orm::DbErr::Custom("Failed to get last_name".to_string()))?,
-                created_at: row.try_get::<NaiveDateTime>("", "created_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get created_at".to_string()))?,
-                updated_at: row.try_get::<NaiveDateTime>("", "updated_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get updated_at".to_string()))?,
-            });
+        let mut users = result
+            .iter()
+            .map(user_from_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        if truncate_to_cap(&mut users, cap) {
+            warn!(cap = cap, "Unpaginated user listing truncated at row cap; use get_users_paginated instead");
         }
         
         Ok(users)
     }
     
+    /// Returns a single page of users together with the total number of users. `page` is
+    /// 1-based; a `page_size` of 0 is treated as 1.
+    pub async fn get_users_paginated(&self, page: u64, page_size: u64) -> Result<(Vec<UserResponse>, u64), sea_orm::DbErr> {
+        let page_size = page_size.max(1);
+        let offset = page.saturating_sub(1).saturating_mul(page_size);
+        
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT id, email, first_name, last_name, created_at, updated_at FROM users ORDER BY created_at, id LIMIT $1 OFFSET $2",
+            vec![(page_size as i64).into(), (offset as i64).into()]
+        );
+        
+        let result = self.db.query_all(stmt).await?;
+        let users = result.iter().map(user_from_row).collect::<Result<Vec<_>, _>>()?;
+        
+        let count_stmt = Statement::from_string(DatabaseBackend::Postgres, "SELECT COUNT(*) AS count FROM users");
+        let total_count = match self.db.query_one(count_stmt).await? {
+            Some(row) => row.try_get::<i64>("", "count")? as u64,
+            None => 0,
+        };
+        
+        Ok((users, total_count))
+    }
+    
     pub async fn get_user(&self, user_id: &str) -> Result<Option<UserResponse>, sea_orm::DbErr> {
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
@@ -76,14 +122,7 @@ impl TenantService {
         let result = self.db.query_one(stmt).await?;
         
         if let Some(row) = result {
-            Ok(Some(UserResponse {
-                id: row.try_get::<String>("", "id").map_err(|_| sea_orm::DbErr::Custom("Failed to get id".to_string()))?,
-                email: row.try_get::<String>("", "email").map_err(|_| sea_orm::DbErr::Custom("Failed to get email".to_string()))?,
-                first_name: row.try_get::<String>("", "first_name").map_err(|_| sea_orm::DbErr::Custom("Failed to get first_name".to_string()))?,
-                last_name: row.try_get::<String>("", "last_name").map_err(|_| sea_orm::DbErr::Custom("Failed to get last_name".to_string()))?,
-                created_at: row.try_get::<NaiveDateTime>("", "created_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get created_at".to_string()))?,
-                updated_at: row.try_get::<NaiveDateTime>("", "updated_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get updated_at".to_string()))?,
-            }))
+            Ok(Some(user_from_row(&row)?))
         } else {
             Ok(None)
         }
@@ -94,29 +133,21 @@ impl TenantService {
         
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "UPDATE users SET email = $1, first_name = $2, last_name = $3, updated_at = $4 WHERE id = $5",
+            // RETURNING reports the stored created_at rather than reconstructing the row
+            "UPDATE users SET email = $1, first_name = $2, last_name = $3, updated_at = $4, version = version + 1 WHERE id = $5 \
+             RETURNING id, email, first_name, last_name, created_at, updated_at",
             vec![
-                user_data.email.clone().into(),
-                user_data.first_name.clone().into(),
-                user_data.last_name.clone().into(),
+                user_data.email.trim().to_lowercase().into(),
+                user_data.first_name.into(),
+                user_data.last_name.into(),
                 now.into(),
                 user_id.into()
             ]
         );
         
-        let result = self.db.execute(stmt).await?;
-        
-        if result.rows_affected() > 0 {
-            Ok(Some(UserResponse {
-                id: user_id.to_string(),
-                email: user_data.email,
-                first_name: user_data.first_name,
-                last_name: user_data.last_name,
-                created_at: Utc::now().naive_utc(), // Would get from database
-                updated_at: now,
-            }))
-        } else {
-            Ok(None)
+        match self.db.query_one(stmt).await? {
+            Some(row) => Ok(Some(user_from_row(&row)?)),
+            None => Ok(None),
         }
     }
     
@@ -131,4 +162,19 @@ impl TenantService {
         
         Ok(result.rows_affected() > 0)
     }
-} 
\ No newline at end of file
+} 
+
+fn user_from_row(row: &QueryResult) -> Result<UserResponse, sea_orm::DbErr> {
+    let first_name = row.try_get::<String>("", "first_name").map_err(|_| sea_orm::DbErr::Custom("Failed to get first_name".to_string()))?;
+    let last_name = row.try_get::<String>("", "last_name").map_err(|_| sea_orm::DbErr::Custom("Failed to get last_name".to_string()))?;
+    
+    Ok(UserResponse {
+        id: row.try_get::<String>("", "id").map_err(|_| sea_orm::DbErr::Custom("Failed to get id".to_string()))?,
+        email: row.try_get::<String>("", "email").map_err(|_| sea_orm::DbErr::Custom("Failed to get email".to_string()))?,
+        display_name: display_name(&first_name, &last_name),
+        first_name,
+        last_name,
+        created_at: row.try_get::<NaiveDateTime>("", "created_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get created_at".to_string()))?,
+        updated_at: row.try_get::<NaiveDateTime>("", "updated_at").map_err(|_| sea_orm::DbErr::Custom("Failed to get updated_at".to_string()))?,
+    })
+}
\ No newline at end of file
@@ -1,108 +1,814 @@
-use sea_orm::{Database, DatabaseConnection, Statement, DatabaseBackend, ConnectionTrait};
+use sea_orm::{
+    AccessMode, ConnectOptions, ConnectionTrait, Database, DatabaseBackend, DatabaseConnection, Statement,
+    TransactionTrait,
+};
 use sea_orm_migration::MigratorTrait;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use tokio::sync::{OnceCell, RwLock};
+use tracing::warn;
 use anyhow::Result;
-use crate::types::config::DatabaseConfig;
+use crate::database::connect_to_master_database;
+use crate::pagination::paginate_all;
+use crate::types::config::{DatabaseConfig, ProductUniqueKey, TenancyStrategy};
+use super::schema_drift::{SchemaDrift, detect_schema_drift};
 
-#[derive(Clone, Debug)]
+/// Callback invoked with the tenant id whenever a cached tenant connection is evicted.
+pub type EvictionHook = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// A tenant is placed in a region whose database cluster this deployment has no route to.
+///
+/// Surfaced to clients as 421, so a regional gateway can send the request elsewhere.
+#[derive(Debug, thiserror::Error)]
+#[error("Tenant '{tenant_id}' is served from region '{region}'")]
+pub struct MisdirectedTenant {
+    pub tenant_id: String,
+    pub region: String,
+}
+
+/// Unique index on product names, present only while `PRODUCT_UNIQUE_KEY=name`. SKUs have
+/// their own unique index from the tenant migrations in either mode.
+pub const PRODUCT_NAME_INDEX: &str = "idx_products_name";
+
+/// Window over which the eviction rate is measured.
+const EVICTION_WINDOW: Duration = Duration::from_secs(60);
+
+/// Snapshot of the tenant connection cache, for judging whether its capacity fits the load.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConnectionStats {
+    pub cached_connections: usize,
+    pub capacity: usize,
+    /// `cached_connections / capacity`, from 0.0 to 1.0.
+    pub utilization: f64,
+    pub evictions_total: u64,
+    pub evictions_last_minute: usize,
+}
+
+/// Which tenant migrations have been applied to a tenant database and how many are pending.
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationStatus {
+    /// Names of applied migrations, oldest first.
+    pub applied: Vec<String>,
+    pub pending_count: usize,
+}
+
+/// Result of bringing one tenant database up to date with the tenant migrations.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum MigrationOutcome {
+    /// `applied` pending migrations were run; 0 means the database was already current.
+    Migrated { applied: usize },
+    Failed { error: String },
+}
+
+/// A cached tenant pool, when the tenant was last confirmed to be active, and when the pool
+/// was last handed out.
+#[derive(Debug)]
+struct CachedConnection {
+    connection: DatabaseConnection,
+    validated_at: Instant,
+    last_used: Instant,
+}
+
+impl CachedConnection {
+    /// Whether the tenant was confirmed active recently enough, as of `now`, to hand out the
+    /// pool without re-checking its status. A `recheck_after` of zero re-checks every time.
+    fn is_fresh(&self, now: Instant, recheck_after: Duration) -> bool {
+        now.saturating_duration_since(self.validated_at) < recheck_after
+    }
+}
+
+/// The tenant whose cached pool has gone unused the longest, which is evicted first.
+fn least_recently_used(connections: &HashMap<String, CachedConnection>) -> Option<String> {
+    connections
+        .iter()
+        .min_by_key(|(_, cached)| cached.last_used)
+        .map(|(tenant_id, _)| tenant_id.clone())
+}
+
+/// Recent eviction times, used to compute the eviction rate and throttle churn warnings.
+#[derive(Debug, Default)]
+struct EvictionWindow {
+    times: VecDeque<Instant>,
+    total: u64,
+    last_warning: Option<Instant>,
+}
+
+impl EvictionWindow {
+    fn prune(&mut self, now: Instant) {
+        while self.times.front().is_some_and(|time| now.duration_since(*time) > EVICTION_WINDOW) {
+            self.times.pop_front();
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct TenantConnectionManager {
-    connections: Arc<RwLock<HashMap<String, DatabaseConnection>>>,
+    connections: Arc<RwLock<HashMap<String, CachedConnection>>>,
     master_connection: DatabaseConnection,
+    /// Pool on the maintenance database used for `CREATE`/`DROP DATABASE`, opened on first use.
+    admin_connection: Arc<OnceCell<DatabaseConnection>>,
     config: DatabaseConfig,
     max_connections_per_tenant: usize,
+    on_evict: Option<EvictionHook>,
+    product_unique_key: ProductUniqueKey,
+    closed_connections: Arc<AtomicU64>,
+    evictions: Arc<Mutex<EvictionWindow>>,
+}
+
+impl fmt::Debug for TenantConnectionManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantConnectionManager")
+            .field("connections", &self.connections)
+            .field("master_connection", &self.master_connection)
+            .field("admin_connection", &self.admin_connection)
+            .field("config", &self.config)
+            .field("max_connections_per_tenant", &self.max_connections_per_tenant)
+            .field("on_evict", &self.on_evict.is_some())
+            .field("product_unique_key", &self.product_unique_key)
+            .field("closed_connections", &self.closed_connections)
+            .field("evictions", &self.evictions)
+            .finish()
+    }
 }
 
 impl TenantConnectionManager {
     pub async fn new(config: DatabaseConfig) -> Result<Self> {
-        let master_connection = Database::connect(&config.master_url).await?;
+        let master_connection = connect_to_master_database(&config).await?;
+        let max_connections_per_tenant = config.max_cached_tenants;
         
         Ok(Self {
             connections: Arc::new(RwLock::new(HashMap::new())),
             master_connection,
+            admin_connection: Arc::new(OnceCell::new()),
             config,
-            max_connections_per_tenant: 10,
+            max_connections_per_tenant,
+            on_evict: None,
+            product_unique_key: ProductUniqueKey::default(),
+            closed_connections: Arc::new(AtomicU64::new(0)),
+            evictions: Arc::new(Mutex::new(EvictionWindow::default())),
         })
     }
     
+    /// Registers a callback that runs with the tenant id each time a cached connection is
+    /// evicted, e.g. to flush tenant-scoped caches or emit metrics.
+    pub fn with_eviction_hook(mut self, hook: impl Fn(&str) + Send + Sync + 'static) -> Self {
+        self.on_evict = Some(Arc::new(hook));
+        self
+    }
+    
+    /// Sets the product field tenant databases enforce uniqueness on, which decides whether
+    /// migrating a tenant creates or drops `PRODUCT_NAME_INDEX`.
+    pub fn with_product_unique_key(mut self, unique_key: ProductUniqueKey) -> Self {
+        self.product_unique_key = unique_key;
+        self
+    }
+    
     pub async fn get_tenant_connection(&self, tenant_id: &str) -> Result<DatabaseConnection> {
-        let mut connections = self.connections.write().await;
+        validate_tenant_id(tenant_id)?;
+        
+        let recheck_after = Duration::from_secs(self.config.tenant_status_recheck_secs);
         
-        if let Some(conn) = connections.get(tenant_id) {
-            return Ok(conn.clone());
+        if let Some(cached) = self.connections.write().await.get_mut(tenant_id)
+            && cached.is_fresh(Instant::now(), recheck_after)
+        {
+            cached.last_used = Instant::now();
+            return Ok(cached.connection.clone());
         }
         
-        // Validate tenant exists and is active
-        self.validate_tenant(tenant_id).await?;
+        // The status check and connecting run without the cache lock, so a slow or unreachable
+        // tenant doesn't hold up requests for the others.
         
-        // Create new connection for this tenant
-        let db_url = self.build_tenant_db_url(tenant_id);
-        let connection = Database::connect(&db_url).await?;
+        // Validate tenant exists and is active, and find which cluster holds its database.
+        // Cached pools are re-checked too, so a suspended tenant loses access within
+        // `tenant_status_recheck_secs`.
+        let region = match self.tenant_region(tenant_id).await {
+            Ok(region) => region,
+            Err(e) => {
+                self.invalidate_tenant(tenant_id).await;
+                return Err(e);
+            }
+        };
         
-        // Limit connections per tenant
-        if connections.len() >= self.max_connections_per_tenant {
-            // Remove oldest connection (LRU could be implemented here)
-            connections.clear();
+        if let Some(cached) = self.connections.write().await.get_mut(tenant_id) {
+            let now = Instant::now();
+            cached.validated_at = now;
+            cached.last_used = now;
+            return Ok(cached.connection.clone());
         }
         
-        connections.insert(tenant_id.to_string(), connection.clone());
+        // Create new connection for this tenant
+        // Opens `tenant_min_connections` eagerly, so the first queries find a ready pool
+        let connection = Database::connect(self.tenant_connect_options(tenant_id, region.as_deref())?).await?;
+        
+        let (connection, evicted, duplicate) = {
+            let mut connections = self.connections.write().await;
+            let now = Instant::now();
+            
+            if let Some(cached) = connections.get_mut(tenant_id) {
+                // A concurrent request cached a pool while this one connected; use theirs
+                cached.last_used = now;
+                (cached.connection.clone(), None, Some(connection))
+            } else {
+                // Limit connections per tenant
+                let evicted = if connections.len() >= self.max_connections_per_tenant {
+                    least_recently_used(&connections)
+                        .and_then(|id| connections.remove_entry(&id))
+                        .map(|(id, cached)| (id, cached.connection))
+                } else {
+                    None
+                };
+                
+                connections.insert(
+                    tenant_id.to_string(),
+                    CachedConnection { connection: connection.clone(), validated_at: now, last_used: now },
+                );
+                
+                (connection, evicted, None)
+            }
+        };
+        
+        // Closing waits on the pool, so only do it once the lock has been released
+        if let Some(duplicate) = duplicate
+            && let Err(e) = duplicate.close().await
+        {
+            warn!(tenant_id = tenant_id, error = %e, "Failed to close duplicate tenant connection");
+        }
+        if let Some((evicted_id, evicted_connection)) = evicted {
+            self.evict(&evicted_id, evicted_connection).await;
+        }
         
         Ok(connection)
     }
     
+    /// Opens pools for active tenants ahead of their first request, up to the cache capacity,
+    /// and returns how many were warmed.
+    ///
+    /// Tenants that can't be reached are logged and skipped.
+    pub async fn warmup_active_tenants(&self) -> Result<usize> {
+        let mut warmed = 0;
+        let mut pages = paginate_all(self.config.sweep_batch_size, |offset, limit| self.active_tenant_ids(offset, limit));
+        
+        while self.cache_room().await > 0 && let Some(batch) = pages.next_batch().await? {
+            warmed += self.warmup(&batch).await;
+        }
+        
+        Ok(warmed)
+    }
+    
+    /// Opens pools for the given tenants, stopping once the cache is full so warm-up never
+    /// evicts connections, and returns how many were warmed.
+    ///
+    /// Tenants that can't be reached are logged and skipped.
+    pub async fn warmup(&self, tenant_ids: &[String]) -> usize {
+        let mut warmed = 0;
+        
+        for tenant_id in tenant_ids {
+            if self.cache_room().await == 0 {
+                break;
+            }
+            match self.get_tenant_connection(tenant_id).await {
+                Ok(_) => warmed += 1,
+                Err(e) => {
+                    warn!(tenant_id = %tenant_id, error = %e, "Failed to warm up tenant connection");
+                }
+            }
+        }
+        
+        warmed
+    }
+    
+    /// How many more tenant connections fit in the cache before eviction starts.
+    async fn cache_room(&self) -> usize {
+        self.max_connections_per_tenant
+            .saturating_sub(self.connections.read().await.len())
+    }
+    
+    /// Closes an evicted connection's pool and notifies the eviction hook.
+    ///
+    /// Must be called without holding the connections lock.
+    async fn evict(&self, tenant_id: &str, connection: DatabaseConnection) {
+        self.record_eviction();
+        
+        match connection.close().await {
+            Ok(()) => {
+                self.closed_connections.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                warn!(tenant_id = tenant_id, error = %e, "Failed to close evicted tenant connection");
+            }
+        }
+        
+        if let Some(hook) = &self.on_evict {
+            hook(tenant_id);
+        }
+    }
+    
+    /// Removes and closes every cached tenant connection and the master connection pool.
+    ///
+    /// Intended for shutdown; connections requested afterwards are opened afresh.
+    pub async fn close_all(&self) {
+        let connections: Vec<_> = self.connections.write().await.drain().collect();
+        
+        for (tenant_id, cached) in connections {
+            if let Err(e) = cached.connection.close().await {
+                warn!(tenant_id = %tenant_id, error = %e, "Failed to close tenant connection");
+            }
+        }
+        
+        if let Err(e) = self.master_connection.clone().close().await {
+            warn!(error = %e, "Failed to close master connection");
+        }
+        
+        if let Some(admin_connection) = self.admin_connection.get()
+            && let Err(e) = admin_connection.clone().close().await
+        {
+            warn!(error = %e, "Failed to close admin connection");
+        }
+    }
+    
+    /// Removes and closes the tenant's cached connection pool, if any, so the next request
+    /// re-checks the tenant's status before connecting. Call after suspending or deleting a
+    /// tenant.
+    pub async fn invalidate_tenant(&self, tenant_id: &str) {
+        let cached = self.connections.write().await.remove(tenant_id);
+        if let Some(cached) = cached {
+            self.evict(tenant_id, cached.connection).await;
+        }
+    }
+    
+    /// Counts an eviction and warns, at most once a minute, when the eviction rate exceeds
+    /// `eviction_warn_per_minute`: steady churn means the cache is too small for the number of
+    /// active tenants.
+    fn record_eviction(&self) {
+        let now = Instant::now();
+        let mut window = self.evictions.lock().unwrap_or_else(|e| e.into_inner());
+        window.prune(now);
+        window.times.push_back(now);
+        window.total += 1;
+        
+        let threshold = self.config.eviction_warn_per_minute as usize;
+        let recently_warned = window
+            .last_warning
+            .is_some_and(|time| now.duration_since(time) < EVICTION_WINDOW);
+        if threshold > 0 && window.times.len() > threshold && !recently_warned {
+            window.last_warning = Some(now);
+            warn!(
+                evictions_last_minute = window.times.len(),
+                capacity = self.max_connections_per_tenant,
+                "Tenant connection cache is churning; consider raising its capacity"
+            );
+        }
+    }
+    
+    /// Reports cache occupancy against capacity and the recent eviction rate.
+    pub async fn stats(&self) -> ConnectionStats {
+        let cached_connections = self.connections.read().await.len();
+        let (evictions_total, evictions_last_minute) = {
+            let mut window = self.evictions.lock().unwrap_or_else(|e| e.into_inner());
+            window.prune(Instant::now());
+            (window.total, window.times.len())
+        };
+        
+        ConnectionStats {
+            cached_connections,
+            capacity: self.max_connections_per_tenant,
+            utilization: cached_connections as f64 / self.max_connections_per_tenant.max(1) as f64,
+            evictions_total,
+            evictions_last_minute,
+        }
+    }
+    
+    /// Number of evicted tenant connections that have been closed so far.
+    pub fn closed_connection_count(&self) -> u64 {
+        self.closed_connections.load(Ordering::Relaxed)
+    }
+    
+    /// Compares the tenant's database against the schema expected from its migrations.
+    pub async fn detect_drift(&self, tenant_id: &str) -> Result<Vec<SchemaDrift>> {
+        let connection = self.get_tenant_connection(tenant_id).await?;
+        Ok(detect_schema_drift(&connection).await?)
+    }
+    
+    /// Reports the tenant database's applied migrations and how many are still pending.
+    pub async fn tenant_migration_status(&self, tenant_id: &str) -> Result<MigrationStatus> {
+        let connection = self.get_tenant_connection(tenant_id).await?;
+        
+        let applied = tenant_migration::TenantMigrator::get_applied_migrations(&connection)
+            .await?
+            .iter()
+            .map(|migration| migration.name().to_string())
+            .collect();
+        let pending_count = tenant_migration::TenantMigrator::get_pending_migrations(&connection)
+            .await?
+            .len();
+        
+        Ok(MigrationStatus { applied, pending_count })
+    }
+    
+    /// Applies pending tenant migrations to every active tenant database, returning each
+    /// tenant's outcome in tenant id order.
+    ///
+    /// A tenant that fails is recorded as `Failed` and the run moves on to the next one; only a
+    /// failure to list tenants aborts the run.
+    pub async fn migrate_all_tenants(&self) -> Result<Vec<(String, MigrationOutcome)>> {
+        let mut outcomes = Vec::new();
+        let mut pages = paginate_all(self.config.sweep_batch_size, |offset, limit| self.active_tenant_ids(offset, limit));
+        
+        while let Some(batch) = pages.next_batch().await? {
+            for tenant_id in batch {
+                let outcome = match self.migrate_tenant(&tenant_id).await {
+                    Ok(applied) => MigrationOutcome::Migrated { applied },
+                    Err(e) => {
+                        warn!(tenant_id = %tenant_id, error = %e, "Failed to migrate tenant database");
+                        MigrationOutcome::Failed { error: e.to_string() }
+                    }
+                };
+                outcomes.push((tenant_id, outcome));
+            }
+        }
+        
+        Ok(outcomes)
+    }
+    
+    /// Runs the tenant's pending migrations and syncs the product name index, returning how
+    /// many migrations there were.
+    async fn migrate_tenant(&self, tenant_id: &str) -> Result<usize> {
+        let connection = self.get_tenant_connection(tenant_id).await?;
+        let pending = tenant_migration::TenantMigrator::get_pending_migrations(&connection)
+            .await?
+            .len();
+        tenant_migration::TenantMigrator::up(&connection, None).await?;
+        self.apply_product_unique_key(&connection).await?;
+        Ok(pending)
+    }
+    
+    /// Creates the product name index in `name` mode and drops it in `sku` mode. Creating it
+    /// fails while the tenant still has products sharing a name.
+    async fn apply_product_unique_key(&self, connection: &DatabaseConnection) -> Result<()> {
+        let sql = match self.product_unique_key {
+            ProductUniqueKey::Name => format!(
+                "CREATE UNIQUE INDEX IF NOT EXISTS {} ON products (name)",
+                PRODUCT_NAME_INDEX
+            ),
+            ProductUniqueKey::Sku => format!("DROP INDEX IF EXISTS {}", PRODUCT_NAME_INDEX),
+        };
+        connection.execute(Statement::from_string(DatabaseBackend::Postgres, sql)).await?;
+        Ok(())
+    }
+    
+    /// Runs drift detection for every active tenant, returning only tenants that drifted.
+    ///
+    /// Tenants whose database can't be reached are logged and skipped so one broken tenant
+    /// doesn't hide drift in the others.
+    pub async fn detect_drift_all(&self) -> Result<BTreeMap<String, Vec<SchemaDrift>>> {
+        let mut report = BTreeMap::new();
+        let mut pages = paginate_all(self.config.sweep_batch_size, |offset, limit| self.active_tenant_ids(offset, limit));
+        
+        while let Some(batch) = pages.next_batch().await? {
+            for tenant_id in batch {
+                match self.detect_drift(&tenant_id).await {
+                    Ok(drift) if drift.is_empty() => {}
+                    Ok(drift) => {
+                        report.insert(tenant_id, drift);
+                    }
+                    Err(e) => {
+                        warn!(tenant_id = %tenant_id, error = %e, "Failed to check tenant schema drift");
+                    }
+                }
+            }
+        }
+        
+        Ok(report)
+    }
+    
+    /// One page of active tenant ids, ordered by id; sweeps walk these with `paginate_all`.
+    async fn active_tenant_ids(&self, offset: u64, limit: u64) -> Result<Vec<String>> {
+        let stmt = Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT id FROM tenants WHERE status = 'active' ORDER BY id LIMIT $1 OFFSET $2",
+            vec![(limit as i64).into(), (offset as i64).into()]
+        );
+        
+        let rows = self.master_connection.query_all(stmt).await?;
+        Ok(rows
+            .iter()
+            .map(|row| row.try_get::<String>("", "id"))
+            .collect::<Result<_, _>>()?)
+    }
+    
+    /// Runs the configured validation query against the master database to confirm it is reachable.
+    pub async fn ping_master(&self) -> Result<()> {
+        self.run_validation_query(&self.master_connection).await
+    }
+    
+    /// Opens (or reuses) a tenant's connection and runs the validation query on it.
+    pub async fn ping_tenant(&self, tenant_id: &str) -> Result<()> {
+        let connection = self.get_tenant_connection(tenant_id).await?;
+        self.run_validation_query(&connection).await
+    }
+    
+    /// Runs the validation query in a read-only transaction that is then rolled back. Config
+    /// parsing only screens out obvious writes; here Postgres itself rejects any write the
+    /// query attempts, e.g. through a side-effecting function.
+    async fn run_validation_query(&self, connection: &DatabaseConnection) -> Result<()> {
+        let txn = connection.begin_with_config(None, Some(AccessMode::ReadOnly)).await?;
+        let stmt = Statement::from_string(DatabaseBackend::Postgres, self.config.validation_query.clone());
+        txn.execute(stmt).await?;
+        txn.rollback().await?;
+        Ok(())
+    }
+    
     pub async fn get_master_connection(&self) -> DatabaseConnection {
         self.master_connection.clone()
     }
     
-    async fn validate_tenant(&self, tenant_id: &str) -> Result<()> {
+    /// Looks up an active tenant's region, failing if the tenant doesn't exist or is inactive.
+    async fn tenant_region(&self, tenant_id: &str) -> Result<Option<String>> {
         // Use existing master connection to check tenant status
         let stmt = Statement::from_sql_and_values(
             DatabaseBackend::Postgres,
-            "SELECT id, status FROM tenants WHERE id = $1 AND status = 'active'",
+            "SELECT id, region FROM tenants WHERE id = $1 AND status = 'active'",
             vec![tenant_id.into()]
         );
         
-        let tenant = self.master_connection.query_one(stmt).await?;
-        
-        if tenant.is_some() {
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("Tenant not found or inactive"))
+        match self.master_connection.query_one(stmt).await? {
+            Some(row) => Ok(row.try_get::<Option<String>>("", "region")?),
+            None => Err(anyhow::anyhow!("Tenant not found or inactive")),
+        }
+    }
+    
+    /// Whether tenants can be placed in `region`, i.e. it has a cluster in `DB_REGIONS`.
+    pub fn serves_region(&self, region: &str) -> bool {
+        self.config.regions.contains_key(region)
+    }
+    
+    /// Host and port of the cluster holding databases for `region`; `None` is the default cluster.
+    fn cluster_address(&self, tenant_id: &str, region: Option<&str>) -> Result<(&str, u16)> {
+        match region {
+            None => Ok((&self.config.host, self.config.port)),
+            Some(region) => {
+                let cluster = self.config.regions.get(region).ok_or_else(|| MisdirectedTenant {
+                    tenant_id: tenant_id.to_string(),
+                    region: region.to_string(),
+                })?;
+                Ok((&cluster.host, cluster.port))
+            }
         }
     }
     
-    fn build_tenant_db_url(&self, tenant_id: &str) -> String {
-        format!(
-            "postgresql://{}:{}@{}:{}/tenant_{}",
+    /// URL of the database holding the tenant's tables: its own database, or the shared one
+    /// under `SchemaPerTenant`.
+    fn build_tenant_db_url(&self, tenant_id: &str, region: Option<&str>) -> Result<String> {
+        let (host, port) = self.cluster_address(tenant_id, region)?;
+        let database = match self.config.tenancy_strategy {
+            TenancyStrategy::DatabasePerTenant => tenant_namespace(tenant_id),
+            TenancyStrategy::SchemaPerTenant => self.config.shared_tenant_database.clone(),
+        };
+        Ok(self.config.with_ssl_mode(&format!(
+            "postgresql://{}:{}@{}:{}/{}",
+            self.config.username,
+            self.config.password,
+            host,
+            port,
+            database
+        )))
+    }
+    
+    /// Pool options for the tenant's connections. Under `SchemaPerTenant`, every connection
+    /// gets `search_path` set to the tenant's schema, so queries and migrations only see its tables.
+    fn tenant_connect_options(&self, tenant_id: &str, region: Option<&str>) -> Result<ConnectOptions> {
+        let mut options = self.config.tenant_connect_options(&self.build_tenant_db_url(tenant_id, region)?);
+        if self.config.tenancy_strategy == TenancyStrategy::SchemaPerTenant {
+            options.set_schema_search_path(tenant_namespace(tenant_id));
+        }
+        Ok(options)
+    }
+    
+    /// URL of the maintenance database on the default cluster. `ADMIN_DATABASE_URL` overrides it.
+    fn build_admin_db_url(&self) -> String {
+        if let Some(admin_url) = &self.config.admin_url {
+            return self.config.with_ssl_mode(admin_url);
+        }
+
+        self.config.with_ssl_mode(&format!(
+            "postgresql://{}:{}@{}:{}/postgres",
             self.config.username,
             self.config.password,
             self.config.host,
-            self.config.port,
-            tenant_id
-        )
+            self.config.port
+        ))
     }
     
-    pub async fn create_tenant_database(&self, tenant_id: &str) -> Result<()> {
-        // Connect to postgres database to create new database
-        let admin_db = Database::connect("postgresql://postgres@localhost/postgres").await?;
-        
-        // Create new database
-        let db_name = format!("tenant_{}", tenant_id);
-        let stmt = Statement::from_string(
-            DatabaseBackend::Postgres,
-            format!("CREATE DATABASE {}", db_name)
-        );
-        admin_db.execute(stmt).await?;
+    /// Runs a `CREATE`/`DROP DATABASE` statement on the cluster for `region`.
+    ///
+    /// The default cluster goes through the shared admin pool; regional clusters get a
+    /// connection for the statement alone, since DDL there is rare.
+    async fn execute_admin(&self, tenant_id: &str, region: Option<&str>, sql: String) -> Result<()> {
+        if region.is_none() {
+            let stmt = Statement::from_string(DatabaseBackend::Postgres, sql);
+            self.admin_connection().await?.execute(stmt).await?;
+            return Ok(());
+        }
         
-        // Run migrations on new database
-        let tenant_db_url = self.build_tenant_db_url(tenant_id);
-        self.run_tenant_migrations(&tenant_db_url).await
+        let (host, port) = self.cluster_address(tenant_id, region)?;
+        let url = self.config.with_ssl_mode(&format!(
+            "postgresql://{}:{}@{}:{}/postgres",
+            self.config.username,
+            self.config.password,
+            host,
+            port
+        ));
+        self.execute_once(&url, sql).await
+    }
+    
+    /// Runs a `CREATE`/`DROP SCHEMA` statement on the shared tenant database of the cluster for
+    /// `region`, over a connection for the statement alone.
+    async fn execute_shared(&self, tenant_id: &str, region: Option<&str>, sql: String) -> Result<()> {
+        let url = self.build_tenant_db_url(tenant_id, region)?;
+        self.execute_once(&url, sql).await
     }
     
-    async fn run_tenant_migrations(&self, db_url: &str) -> Result<()> {
-        let db = Database::connect(db_url).await?;
-        tenant_migration::TenantMigrator::up(&db, None).await?;
+    /// Runs one statement over a connection opened and closed just for it.
+    async fn execute_once(&self, url: &str, sql: String) -> Result<()> {
+        let db = Database::connect(self.config.admin_connect_options(url)).await?;
+        let executed = db.execute(Statement::from_string(DatabaseBackend::Postgres, sql)).await;
+        db.close().await?;
+        executed?;
         Ok(())
     }
-} 
\ No newline at end of file
+    
+    /// The shared admin pool, connecting it on first use. Sized by `admin_max_connections`.
+    async fn admin_connection(&self) -> Result<&DatabaseConnection> {
+        Ok(self
+            .admin_connection
+            .get_or_try_init(|| Database::connect(self.config.admin_connect_options(&self.build_admin_db_url())))
+            .await?)
+    }
+    
+    /// Creates and migrates the tenant's database, or its schema under `SchemaPerTenant`, on
+    /// the cluster for `region`.
+    pub async fn create_tenant_database(&self, tenant_id: &str, region: Option<&str>) -> Result<()> {
+        // The id is interpolated into DDL below, which cannot take bind parameters
+        validate_tenant_id(tenant_id)?;
+        
+        let name = tenant_namespace(tenant_id);
+        match self.config.tenancy_strategy {
+            TenancyStrategy::DatabasePerTenant => {
+                self.execute_admin(tenant_id, region, format!("CREATE DATABASE {}", name)).await?
+            }
+            TenancyStrategy::SchemaPerTenant => {
+                self.execute_shared(tenant_id, region, format!("CREATE SCHEMA {}", name)).await?
+            }
+        }
+        
+        // Run migrations on new database, dropping it again if they fail so the id can be reused
+        let options = self.tenant_connect_options(tenant_id, region)?;
+        if let Err(e) = self.run_tenant_migrations(options).await {
+            if let Err(drop_error) = self.drop_tenant_database(tenant_id, region).await {
+                warn!(tenant_id = tenant_id, error = %drop_error, "Failed to drop partially created tenant database");
+            }
+            return Err(e);
+        }
+        
+        Ok(())
+    }
+    
+    /// Drops the tenant's database, or its schema and everything in it under
+    /// `SchemaPerTenant`, first closing its cached connection pool.
+    ///
+    /// Dropping a database fails if other sessions are still connected to it.
+    pub async fn drop_tenant_database(&self, tenant_id: &str, region: Option<&str>) -> Result<()> {
+        // The id is interpolated into DDL below, which cannot take bind parameters
+        validate_tenant_id(tenant_id)?;
+        
+        self.invalidate_tenant(tenant_id).await;
+        
+        let name = tenant_namespace(tenant_id);
+        match self.config.tenancy_strategy {
+            TenancyStrategy::DatabasePerTenant => {
+                self.execute_admin(tenant_id, region, format!("DROP DATABASE IF EXISTS {}", name)).await
+            }
+            TenancyStrategy::SchemaPerTenant => {
+                self.execute_shared(tenant_id, region, format!("DROP SCHEMA IF EXISTS {} CASCADE", name)).await
+            }
+        }
+    }
+    
+    async fn run_tenant_migrations(&self, options: ConnectOptions) -> Result<()> {
+        let db = Database::connect(options).await?;
+        let migrated = match tenant_migration::TenantMigrator::up(&db, None).await {
+            Ok(()) => self.apply_product_unique_key(&db).await,
+            Err(e) => Err(e.into()),
+        };
+        // Close the pool now rather than on drop, so a failed database can be dropped right away
+        db.close().await?;
+        migrated
+    }
+} 
+
+/// Prefix applied to a tenant id to form its database or schema name.
+const TENANT_DB_PREFIX: &str = "tenant_";
+
+/// Longest tenant id whose database or schema name still fits Postgres' 63-byte identifier limit.
+pub const MAX_TENANT_ID_LENGTH: usize = 63 - TENANT_DB_PREFIX.len();
+
+/// Name of the tenant's database, or of its schema under `SchemaPerTenant`.
+fn tenant_namespace(tenant_id: &str) -> String {
+    format!("{}{}", TENANT_DB_PREFIX, tenant_id)
+}
+
+/// Ensures a tenant id is safe to embed in database URLs and DDL.
+///
+/// Accepted ids are non-empty, consist only of lowercase ASCII letters, digits and
+/// underscores, and are short enough that `tenant_<id>` is a valid Postgres identifier.
+pub fn validate_tenant_id(tenant_id: &str) -> Result<()> {
+    if tenant_id.is_empty() {
+        return Err(anyhow::anyhow!("Tenant id must not be empty"));
+    }
+
+    if tenant_id.len() > MAX_TENANT_ID_LENGTH {
+        return Err(anyhow::anyhow!(
+            "Tenant id must be at most {} characters long",
+            MAX_TENANT_ID_LENGTH
+        ));
+    }
+
+    if !tenant_id
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid tenant id '{}': only lowercase letters, digits and underscores are allowed",
+            tenant_id
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached(last_used: Instant) -> CachedConnection {
+        CachedConnection { connection: DatabaseConnection::Disconnected, validated_at: last_used, last_used }
+    }
+
+    #[test]
+    fn tenant_ids_up_to_the_length_limit_are_valid() {
+        assert_eq!(MAX_TENANT_ID_LENGTH, 56);
+        assert!(validate_tenant_id("acme_corp_2").is_ok());
+        assert!(validate_tenant_id(&"a".repeat(MAX_TENANT_ID_LENGTH)).is_ok());
+    }
+
+    #[test]
+    fn tenant_ids_over_the_length_limit_are_rejected() {
+        // `tenant_` plus the id must fit Postgres' 63-byte identifier limit
+        assert!(validate_tenant_id(&"a".repeat(MAX_TENANT_ID_LENGTH + 1)).is_err());
+    }
+
+    #[test]
+    fn tenant_ids_must_be_lowercase_identifiers() {
+        for tenant_id in ["", "Acme", "acme-corp", "acme corp", "acme;drop", "acmé"] {
+            assert!(validate_tenant_id(tenant_id).is_err(), "accepted {:?}", tenant_id);
+        }
+    }
+
+    #[test]
+    fn least_recently_used_picks_the_oldest_entry() {
+        let now = Instant::now();
+        let connections = HashMap::from([
+            ("acme".to_string(), cached(now - Duration::from_secs(5))),
+            ("globex".to_string(), cached(now - Duration::from_secs(60))),
+            ("initech".to_string(), cached(now)),
+        ]);
+
+        assert_eq!(least_recently_used(&connections).as_deref(), Some("globex"));
+    }
+
+    #[test]
+    fn cached_status_is_trusted_only_within_the_recheck_window() {
+        let now = Instant::now();
+        let recheck_after = Duration::from_secs(30);
+
+        assert!(cached(now - Duration::from_secs(29)).is_fresh(now, recheck_after));
+        assert!(!cached(now - Duration::from_secs(30)).is_fresh(now, recheck_after));
+        assert!(!cached(now - Duration::from_secs(120)).is_fresh(now, recheck_after));
+    }
+
+    #[test]
+    fn zero_recheck_window_rechecks_every_request() {
+        let now = Instant::now();
+        assert!(!cached(now).is_fresh(now, Duration::ZERO));
+    }
+
+    #[test]
+    fn least_recently_used_of_an_empty_cache_is_none() {
+        assert_eq!(least_recently_used(&HashMap::new()), None);
+    }
+}
@@ -0,0 +1,232 @@
+use std::future::Future;
+use sea_orm::{ConnectionTrait, DbErr, EntityTrait, PaginatorTrait, Select};
+use serde::Serialize;
+use crate::error::ApiError;
+
+/// One page of a listing together with where it sits among the other pages.
+#[derive(Debug, Serialize)]
+pub struct Paginated<T> {
+    pub items: Vec<T>,
+    pub total_count: u64,
+    /// `ceil(total_count / page_size)`; 0 when nothing matches.
+    pub total_pages: u64,
+    pub page: u32,
+    pub page_size: u32,
+    pub has_next: bool,
+    pub has_prev: bool,
+}
+
+impl<T> Paginated<T> {
+    /// Wraps page `page` (1-based) of a listing with `total_count` items overall.
+    pub fn new(items: Vec<T>, total_count: u64, page: u32, page_size: u32) -> Self {
+        let total_pages = total_pages(total_count, page_size as u64);
+        Self {
+            items,
+            total_count,
+            total_pages,
+            page,
+            page_size,
+            has_next: (page as u64) < total_pages,
+            has_prev: page > 1,
+        }
+    }
+
+    /// Converts the items, keeping the page metadata.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Paginated<U> {
+        Paginated {
+            items: self.items.into_iter().map(f).collect(),
+            total_count: self.total_count,
+            total_pages: self.total_pages,
+            page: self.page,
+            page_size: self.page_size,
+            has_next: self.has_next,
+            has_prev: self.has_prev,
+        }
+    }
+}
+
+/// Number of pages needed for `total_count` items, `page_size` at a time.
+pub fn total_pages(total_count: u64, page_size: u64) -> u64 {
+    total_count.div_ceil(page_size.max(1))
+}
+
+/// Page size used when a listing request doesn't give one.
+pub const DEFAULT_PAGE_SIZE: u32 = 25;
+
+/// The requested page size, or the default, capped at `max_page_size` (`MAX_PAGE_SIZE`).
+/// Oversized requests are clamped rather than rejected; callers reject 0 with `validate_page`.
+pub fn page_size(requested: Option<u32>, max_page_size: u32) -> u32 {
+    requested.unwrap_or(DEFAULT_PAGE_SIZE).min(max_page_size)
+}
+
+/// Rejects page numbers and sizes below 1; pages are 1-based.
+pub fn validate_page(page: Option<u32>, page_size: Option<u32>) -> Result<(), ApiError> {
+    if page == Some(0) {
+        return Err(ApiError::BadRequest("page must be at least 1".to_string()));
+    }
+    if page_size == Some(0) {
+        return Err(ApiError::BadRequest("page_size must be at least 1".to_string()));
+    }
+    Ok(())
+}
+
+/// Rows an unpaginated listing capped at `cap` should fetch: one extra, so truncation can be
+/// detected without a `COUNT(*)`.
+pub fn unpaginated_limit(cap: u64) -> u64 {
+    cap.saturating_add(1)
+}
+
+/// Drops the rows past `cap` from a listing fetched with `unpaginated_limit`, returning
+/// whether any were dropped so the caller can warn about it.
+pub fn truncate_to_cap<T>(rows: &mut Vec<T>, cap: u64) -> bool {
+    let cap = usize::try_from(cap).unwrap_or(usize::MAX);
+    if rows.len() <= cap {
+        return false;
+    }
+    rows.truncate(cap);
+    true
+}
+
+/// Fetches page `page` (1-based) of `query`, `page_size` rows at a time, along with the total
+/// number of matching rows. Callers validate `page` and `page_size` with `validate_page` first.
+pub async fn paginate<E, C>(
+    query: Select<E>,
+    db: &C,
+    page: u32,
+    page_size: u32,
+) -> Result<Paginated<E::Model>, DbErr>
+where
+    E: EntityTrait,
+    E::Model: Sync,
+    C: ConnectionTrait,
+{
+    let paginator = query.paginate(db, page_size.max(1) as u64);
+    let total_count = paginator.num_items().await?;
+    let items = paginator.fetch_page(page.saturating_sub(1) as u64).await?;
+
+    Ok(Paginated::new(items, total_count, page, page_size))
+}
+
+/// Walks a dataset one batch at a time, so sweeps over every tenant or user never hold more
+/// than `batch_size` rows in memory.
+///
+/// `fetch_page` is called with `(offset, limit)` and must return rows in a stable order. The
+/// walk ends after the first batch shorter than `limit`.
+pub struct PageIter<F> {
+    fetch_page: F,
+    batch_size: u64,
+    offset: u64,
+    done: bool,
+}
+
+/// Starts a batched walk over `fetch_page`. A `batch_size` of 0 is treated as 1.
+pub fn paginate_all<T, E, F, Fut>(batch_size: u64, fetch_page: F) -> PageIter<F>
+where
+    F: FnMut(u64, u64) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, E>>,
+{
+    PageIter {
+        fetch_page,
+        batch_size: batch_size.max(1),
+        offset: 0,
+        done: false,
+    }
+}
+
+impl<F> PageIter<F> {
+    /// Fetches the next batch, or `None` once the dataset is exhausted.
+    pub async fn next_batch<T, E, Fut>(&mut self) -> Result<Option<Vec<T>>, E>
+    where
+        F: FnMut(u64, u64) -> Fut,
+        Fut: Future<Output = Result<Vec<T>, E>>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+
+        let batch = (self.fetch_page)(self.offset, self.batch_size).await?;
+        self.offset += batch.len() as u64;
+        if (batch.len() as u64) < self.batch_size {
+            self.done = true;
+        }
+
+        if batch.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(batch))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn total_pages_rounds_up() {
+        assert_eq!(total_pages(0, 25), 0);
+        assert_eq!(total_pages(25, 25), 1);
+        assert_eq!(total_pages(26, 25), 2);
+        // A zero page size is treated as 1 rather than dividing by zero
+        assert_eq!(total_pages(3, 0), 3);
+    }
+
+    #[test]
+    fn paginated_reports_neighbouring_pages() {
+        let first = Paginated::new(vec![1, 2], 5, 1, 2);
+        assert_eq!(first.total_pages, 3);
+        assert!(first.has_next);
+        assert!(!first.has_prev);
+
+        let last = Paginated::new(vec![5], 5, 3, 2);
+        assert!(!last.has_next);
+        assert!(last.has_prev);
+
+        let empty = Paginated::<u32>::new(Vec::new(), 0, 1, 25);
+        assert_eq!(empty.total_pages, 0);
+        assert!(!empty.has_next);
+    }
+
+    #[test]
+    fn paginated_map_keeps_metadata() {
+        let page = Paginated::new(vec![1, 2], 5, 2, 2).map(|n| n * 10);
+        assert_eq!(page.items, vec![10, 20]);
+        assert_eq!((page.total_count, page.page, page.page_size), (5, 2, 2));
+    }
+
+    #[test]
+    fn page_size_defaults_and_clamps() {
+        assert_eq!(page_size(None, 100), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size(Some(10), 100), 10);
+        assert_eq!(page_size(Some(500), 100), 100);
+        assert_eq!(page_size(None, 10), 10);
+    }
+
+    #[test]
+    fn validate_page_rejects_zero() {
+        assert!(validate_page(None, None).is_ok());
+        assert!(validate_page(Some(1), Some(1)).is_ok());
+        assert!(matches!(validate_page(Some(0), None), Err(ApiError::BadRequest(_))));
+        assert!(matches!(validate_page(None, Some(0)), Err(ApiError::BadRequest(_))));
+    }
+
+    #[test]
+    fn unpaginated_listings_fetch_one_row_past_the_cap() {
+        assert_eq!(unpaginated_limit(10_000), 10_001);
+        assert_eq!(unpaginated_limit(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn rows_past_the_cap_are_dropped() {
+        let mut rows = vec![1, 2, 3, 4];
+        assert!(truncate_to_cap(&mut rows, 3));
+        assert_eq!(rows, vec![1, 2, 3]);
+
+        // Exactly `cap` rows means nothing was cut off
+        assert!(!truncate_to_cap(&mut rows, 3));
+        assert_eq!(rows, vec![1, 2, 3]);
+
+        assert!(truncate_to_cap(&mut rows, 0));
+        assert!(rows.is_empty());
+    }
+}
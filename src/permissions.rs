@@ -0,0 +1,26 @@
+//! Permission names carried in JWTs and stored on users and API clients.
+//!
+//! Use these constants rather than string literals so a typo can't silently deny access.
+
+/// Read user profiles in the caller's tenant.
+pub const USERS_READ: &str = "users:read";
+
+/// Create, update and delete user profiles in the caller's tenant.
+pub const USERS_WRITE: &str = "users:write";
+
+/// Administer the caller's own tenant: grant permissions, read its audit log, inspect its
+/// schema.
+pub const TENANTS_ADMIN: &str = "tenants:admin";
+
+/// Operate across tenants: create, list, migrate and delete tenants, and inspect any tenant.
+/// Also accepted wherever admin endpoints ask for `tenants:admin`.
+pub const PLATFORM_ADMIN: &str = "platform:admin";
+
+/// Every permission above with its description, as seeded into the master `permissions`
+/// catalog at startup.
+pub const DEFAULT_PERMISSIONS: &[(&str, &str)] = &[
+    (USERS_READ, "Read user profiles"),
+    (USERS_WRITE, "Create, update and delete user profiles"),
+    (TENANTS_ADMIN, "Administer the caller's tenant and grant permissions"),
+    (PLATFORM_ADMIN, "Create, migrate and delete tenants and administer any tenant"),
+];
@@ -0,0 +1,27 @@
+use axum::{routing::{delete, get, post, put}, Router};
+use crate::controllers::admin::{audit_index, audit_purge, connection_stats, create_api_client, decode_token, delete_tenant, effective_config, grant_permission, migrate_all_tenants, schema_drift_sweep, set_tenant_status, set_user_permissions, tenant_migration_status, tenant_schema_drift};
+use crate::controllers::auth::{create_tenant, create_tenants_bulk};
+use crate::controllers::tenants::tenants_index;
+use crate::types::shared::AppState;
+
+// Create admin routes; each requires tenants:admin for the caller's own tenant or
+// platform:admin for cross-tenant operations
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/tenants", get(tenants_index).post(create_tenant))
+        .route("/tenants/bulk", post(create_tenants_bulk))
+        .route("/tenants/migrate", post(migrate_all_tenants))
+        .route("/tenants/:tenant_id", delete(delete_tenant))
+        .route("/tenants/:tenant_id/status", put(set_tenant_status))
+        .route("/tenants/:tenant_id/migrations", get(tenant_migration_status))
+        .route("/admin/tenants/drift", get(schema_drift_sweep))
+        .route("/admin/tenants/:tenant_id/drift", get(tenant_schema_drift))
+        .route("/admin/permissions/grant", post(grant_permission))
+        .route("/admin/users/:user_id/permissions", put(set_user_permissions))
+        .route("/admin/api-clients", post(create_api_client))
+        .route("/admin/connections", get(connection_stats))
+        .route("/admin/config", get(effective_config))
+        .route("/admin/jwt/decode", post(decode_token))
+        .route("/audit", get(audit_index))
+        .route("/audit/purge", post(audit_purge))
+}
@@ -0,0 +1,3 @@
+pub mod admin;
+
+pub use admin::routes as admin_routes;
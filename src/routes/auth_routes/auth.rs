@@ -1,5 +1,5 @@
-use axum::{routing::post, Router};
-use crate::controllers::auth::{login, register, create_tenant};
+use axum::{routing::{get, post}, Router};
+use crate::controllers::auth::{login, register, change_password, logout, email_available, email_tenants, issue_client_token, request_password_reset, confirm_password_reset};
 use crate::types::shared::AppState;
 
 // Create auth routes
@@ -7,5 +7,16 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/auth/login", post(login))
         .route("/auth/register", post(register))
-        .route("/tenants", post(create_tenant))
+        .route("/auth/token", post(issue_client_token))
+        .route("/auth/email-available", get(email_available))
+        .route("/auth/tenants", get(email_tenants))
+        .route("/auth/password/reset-request", post(request_password_reset))
+        .route("/auth/password/reset-confirm", post(confirm_password_reset))
+}
+
+// Auth routes that act on the caller's own account; these require a valid JWT
+pub fn protected_routes() -> Router<AppState> {
+    Router::new()
+        .route("/auth/password", post(change_password))
+        .route("/auth/logout", post(logout))
 } 
\ No newline at end of file
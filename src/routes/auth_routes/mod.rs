@@ -1,3 +1,4 @@
 pub mod auth;
 
-pub use auth::routes as auth_routes; 
\ No newline at end of file
+pub use auth::routes as auth_routes;
+pub use auth::protected_routes as account_routes; 
\ No newline at end of file
@@ -1,7 +1,13 @@
 pub mod auth_routes;
 pub mod user_routes;
 pub mod tenant_routes;
+pub mod admin_routes;
+pub mod order_routes;
+pub mod product_routes;
 
-pub use auth_routes::auth_routes;
+pub use auth_routes::{account_routes, auth_routes};
 pub use user_routes::user_routes;
-pub use tenant_routes::tenant_routes; 
\ No newline at end of file
+pub use tenant_routes::tenant_routes;
+pub use admin_routes::admin_routes;
+pub use order_routes::order_routes;
+pub use product_routes::product_routes; 
\ No newline at end of file
@@ -0,0 +1,14 @@
+use axum::{routing::get, Router};
+use crate::controllers::orders::{orders_index, orders_create, orders_update, orders_delete};
+use crate::types::shared::AppState;
+
+// Create order routes with single endpoint pattern
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/orders",
+            get(orders_index)
+            .post(orders_create)
+            .patch(orders_update)
+            .delete(orders_delete)
+        )
+}
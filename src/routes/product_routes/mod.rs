@@ -0,0 +1,3 @@
+pub mod products;
+
+pub use products::routes as product_routes;
@@ -0,0 +1,15 @@
+use axum::{routing::get, Router};
+use crate::controllers::products::{products_index, products_create, products_update, products_delete, products_count};
+use crate::types::shared::AppState;
+
+// Create product routes with single endpoint pattern
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/products",
+            get(products_index)
+            .post(products_create)
+            .patch(products_update)
+            .delete(products_delete)
+        )
+        .route("/api/products/count", get(products_count))
+}
@@ -1,9 +1,11 @@
 use axum::{routing::get, Router};
-use crate::controllers::tenants::health_check;
+use crate::controllers::tenants::{health_check, metrics_export, readiness_check};
 use crate::types::shared::AppState;
 
 // Create tenant routes
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(health_check))
+        .route("/health/ready", get(readiness_check))
+        .route("/metrics", get(metrics_export))
 } 
\ No newline at end of file
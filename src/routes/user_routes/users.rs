@@ -1,5 +1,5 @@
-use axum::{routing::get, Router};
-use crate::controllers::users::{users_index, users_create, users_update, users_delete, users_count};
+use axum::{routing::{delete, get, patch, post}, Router};
+use crate::controllers::users::{users_index, users_create, users_update, users_delete, users_delete_by_path, users_set_active, users_count, users_group_count, users_bulk_create, users_distinct};
 use crate::types::shared::AppState;
 
 // Create user routes with single endpoint pattern
@@ -11,5 +11,10 @@ pub fn routes() -> Router<AppState> {
             .patch(users_update)
             .delete(users_delete)
         )
+        .route("/api/users/:id", delete(users_delete_by_path))
+        .route("/api/users/:id/active", patch(users_set_active))
+        .route("/api/users/bulk", post(users_bulk_create))
         .route("/api/users/count", get(users_count))
+        .route("/api/users/group-count", get(users_group_count))
+        .route("/api/users/distinct", get(users_distinct))
 } 
\ No newline at end of file
@@ -1,9 +1,12 @@
 pub mod shared;
 pub mod config;
 pub mod users;
+pub mod orders;
+pub mod products;
+pub mod normalize;
 
 // Re-export specific items to avoid conflicts
 pub use shared::{TenantContext, AppState, CreateTenantRequest, TenantResponse, CreateUserRequest, LoginRequest, LoginResponse};
 pub use shared::UserResponse as SharedUserResponse; // Rename to avoid conflict
 pub use config::{AppConfig, DatabaseConfig};
-pub use users::{UsersUrlParams, UsersCountUrlParams, UsersRequestBody, UsersResponseType, UserResponse}; 
\ No newline at end of file
+pub use users::{UsersUrlParams, UsersCountUrlParams, UsersGroupCountUrlParams, UsersRequestBody, UsersResponseType, UserResponse, UsersBulkResult, UsersBulkResponse, UsersDistinctUrlParams, DistinctValueCount, UsersDistinctResponse}; 
\ No newline at end of file
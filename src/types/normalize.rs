@@ -0,0 +1,29 @@
+//! Field deserializers that normalize user input at parse time, so handlers always see
+//! canonical values. Apply them with `#[serde(deserialize_with = "...")]`; `Option` fields also
+//! need `#[serde(default)]` so that an absent field still deserializes to `None`.
+
+use serde::{Deserialize, Deserializer};
+
+/// Trims surrounding whitespace and lowercases an email address.
+pub fn email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(value.trim().to_lowercase())
+}
+
+/// [`email`] for optional fields.
+pub fn optional_email<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.map(|value| value.trim().to_lowercase()))
+}
+
+/// Trims surrounding whitespace, keeping the original case (used for names).
+pub fn trimmed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    Ok(value.trim().to_string())
+}
+
+/// [`trimmed`] for optional fields.
+pub fn optional_trimmed<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.map(|value| value.trim().to_string()))
+}
@@ -0,0 +1,3 @@
+pub mod order_types;
+
+pub use order_types::*;
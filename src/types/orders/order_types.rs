@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+use sea_orm::prelude::Decimal;
+
+#[derive(Debug, Deserialize)]
+pub struct OrdersUrlParams {
+    pub id: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub user_id: Option<String>,
+    pub product_id: Option<String>,
+    pub status: Option<String>,
+}
+
+/// Order create/update payload. `total_amount` is always computed server-side from the
+/// product price and quantity, so it is not accepted from clients.
+#[derive(Debug, Deserialize)]
+pub struct OrdersRequestBody {
+    pub id: Option<String>,
+    pub user_id: Option<String>,
+    pub product_id: Option<String>,
+    pub quantity: Option<i32>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub enum OrdersResponseType {
+    SingleOrder(OrderResponse),
+    MultipleOrders(Vec<OrderResponse>),
+    PaginatedOrders {
+        orders: Vec<OrderResponse>,
+        total_count: u64,
+        page: u32,
+        page_size: u32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderResponse {
+    pub id: String,
+    pub user_id: String,
+    pub product_id: String,
+    pub quantity: i32,
+    pub total_amount: Decimal,
+    pub status: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
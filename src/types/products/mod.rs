@@ -0,0 +1,3 @@
+pub mod product_types;
+
+pub use product_types::*;
@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use chrono::NaiveDateTime;
+use sea_orm::prelude::Decimal;
+
+#[derive(Debug, Deserialize)]
+pub struct ProductsUrlParams {
+    pub id: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub name: Option<String>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    /// `total_sold` sorts by total quantity ordered, most first; default is by id.
+    pub sort_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductsCountUrlParams {
+    pub name: Option<String>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProductsRequestBody {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub sku: Option<String>,
+    pub description: Option<String>,
+    pub price: Option<Decimal>,
+}
+
+#[derive(Debug, Serialize)]
+pub enum ProductsResponseType {
+    SingleProduct(ProductResponse),
+    MultipleProducts(Vec<ProductResponse>),
+    PaginatedProducts {
+        products: Vec<ProductResponse>,
+        total_count: u64,
+        page: u32,
+        page_size: u32,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductResponse {
+    pub id: String,
+    pub name: String,
+    pub sku: Option<String>,
+    pub description: Option<String>,
+    pub price: Decimal,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
@@ -6,18 +6,37 @@ pub struct TenantContext {
     pub tenant_id: String,
     pub user_id: String,
     pub permissions: Vec<String>,
+    /// The token's `jti`; empty for tokens issued before token ids were added.
+    pub token_id: String,
+    /// When the token stops being accepted, clock-skew leeway included.
+    pub token_expires_at: NaiveDateTime,
 }
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub tenant_manager: crate::multi_tenancy::TenantConnectionManager,
-    pub jwt_secret: String,
+    pub jwt_keys: crate::middlewares::JwtKeys,
+    pub jwt_expiration: u64,
+    pub rate_limiter: crate::middlewares::RateLimiter,
+    pub revoked_tokens: crate::middlewares::RevokedTokens,
+    pub password_policy: crate::validation::PasswordPolicy,
+    pub max_token_permissions: usize,
+    pub product_unique_key: crate::types::config::ProductUniqueKey,
+    /// Configuration the server started with, for the admin config endpoint.
+    pub config: std::sync::Arc<crate::types::config::AppConfig>,
+    pub metrics: crate::middlewares::MetricsHandle,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateTenantRequest {
-    pub id: String,
+    /// Trimmed and lowercased before validation; a random id is generated when omitted.
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
     pub name: String,
+    /// One of the regions in `DB_REGIONS`; omitted to use the default cluster.
+    #[serde(default)]
+    pub region: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,16 +44,71 @@ pub struct TenantResponse {
     pub id: String,
     pub name: String,
     pub status: String,
+    pub region: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetTenantStatusRequest {
+    /// `active` or `suspended`.
+    pub status: String,
+}
+
+/// Outcome of one entry of a bulk tenant creation, in request order.
+#[derive(Debug, Serialize)]
+pub struct TenantsBulkResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<TenantResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TenantsBulkResponse {
+    pub created: usize,
+    pub failed: usize,
+    pub results: Vec<TenantsBulkResult>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReadinessParams {
+    /// Also check the `HEALTH_CANARY_TENANT` tenant's database.
+    #[serde(default)]
+    pub deep: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TenantsUrlParams {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaginatedTenantsResponse {
+    pub tenants: Vec<TenantResponse>,
+    pub total_count: u64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateUserRequest {
+    // Missing fields default to empty so registration can report them all at once
+    #[serde(default, deserialize_with = "crate::types::normalize::email")]
     pub email: String,
+    #[serde(default)]
     pub password: String,
+    #[serde(default, deserialize_with = "crate::types::normalize::trimmed")]
     pub first_name: String,
+    #[serde(default, deserialize_with = "crate::types::normalize::trimmed")]
     pub last_name: String,
+    /// Permissions for the new account, each of which must be in the `permissions` catalog.
+    /// Defaults to `users:read` and `users:write`; self-registration may not set it.
+    #[serde(default)]
+    pub permissions: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,12 +117,22 @@ pub struct UserResponse {
     pub email: String,
     pub first_name: String,
     pub last_name: String,
+    /// `first_name` and `last_name` joined by a space, skipping whichever is blank.
+    #[serde(default)]
+    pub display_name: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoginRequest {
+    #[serde(deserialize_with = "crate::types::normalize::email")]
     pub email: String,
     pub password: String,
 }
@@ -57,4 +141,180 @@ pub struct LoginRequest {
 pub struct LoginResponse {
     pub token: String,
     pub user: UserResponse,
-} 
\ No newline at end of file
+} 
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordResetRequestResponse {
+    pub message: &'static str,
+    /// Only returned when `PASSWORD_RESET_RETURN_TOKEN` is enabled, for development.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_token: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PasswordResetConfirmRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailAvailabilityParams {
+    pub email: String,
+    pub tenant: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailAvailabilityResponse {
+    pub available: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTenantsParams {
+    pub email: String,
+}
+
+/// Just enough about a tenant to let a user pick it at login.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantSummary {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTenantsResponse {
+    pub tenants: Vec<TenantSummary>,
+}
+
+/// Client-credentials grant for machine clients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientTokenRequest {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientTokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiClientRequest {
+    pub name: String,
+    /// Permissions the client's tokens carry, each from the `permissions` catalog.
+    pub scopes: Vec<String>,
+}
+
+/// A newly registered API client. `client_secret` is only ever returned here.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiClientCreatedResponse {
+    pub client_id: String,
+    pub client_secret: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+}
+
+/// An API client that passed authentication, with the tenant and scopes its token carries.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedClient {
+    pub client_id: String,
+    pub tenant_id: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrantPermissionRequest {
+    pub user_ids: Vec<String>,
+    pub permission: String,
+}
+
+/// Replacement permission set for one user.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetUserPermissionsRequest {
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UserPermissionsResponse {
+    pub user_id: String,
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantStatus {
+    Granted,
+    AlreadyGranted,
+    NotFound,
+    LimitExceeded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionGrantResult {
+    pub user_id: String,
+    pub status: GrantStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrantPermissionResponse {
+    pub permission: String,
+    pub results: Vec<PermissionGrantResult>,
+}
+
+/// A change to record in the audit log. `actor_user_id` is `None` for unauthenticated actions.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub tenant_id: String,
+    pub actor_user_id: Option<String>,
+    /// One of the `crate::audit` action names.
+    pub action: &'static str,
+    pub target_id: String,
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: String,
+    pub tenant_id: String,
+    pub actor_user_id: Option<String>,
+    pub action: String,
+    pub target_id: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditUrlParams {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Another tenant's log; defaults to the caller's tenant.
+    pub tenant_id: Option<String>,
+    pub action: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditPurgeParams {
+    /// Another tenant's log; defaults to the caller's tenant.
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditPurgeResponse {
+    pub tenant_id: String,
+    /// Entries created before this time were removed.
+    pub older_than: NaiveDateTime,
+    pub purged: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecodeTokenRequest {
+    pub token: String,
+}
\ No newline at end of file
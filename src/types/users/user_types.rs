@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 use chrono::NaiveDateTime;
 
@@ -6,28 +7,72 @@ pub struct UsersUrlParams {
     pub id: Option<String>,
     pub page: Option<u32>,
     pub page_size: Option<u32>,
-    pub email: Option<String>,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
+    /// Optional; when given it must be the caller's own tenant, or the request is rejected with 403.
     pub tenant_id: Option<String>,
+    /// One of `id` (default), `email`, `first_name`, `last_name`, `created_at` or `order_count`.
+    pub sort_by: Option<String>,
+    /// `asc` or `desc` (default).
+    pub sort_dir: Option<String>,
+    /// Case-insensitive search across email, first name and last name.
+    pub q: Option<String>,
+    /// Only users created at or after this time (ISO 8601, e.g. `2024-01-31T00:00:00`).
+    pub created_after: Option<NaiveDateTime>,
+    /// Only users created at or before this time; must not precede `created_after`.
+    pub created_before: Option<NaiveDateTime>,
+    /// `true` for active users only, `false` for deactivated ones; both when omitted.
+    pub is_active: Option<bool>,
+    /// Remaining parameters are filters: `email`, `first_name` or `last_name`, optionally
+    /// suffixed with `__eq`, `__startswith` or `__contains` (the default).
+    #[serde(flatten)]
+    pub filters: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct UsersCountUrlParams {
+    /// Must match the caller's tenant if given, as for `UsersUrlParams::tenant_id`.
     pub tenant_id: Option<String>,
-    pub email: Option<String>,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
+    /// Search, as for `UsersUrlParams::q`.
+    pub q: Option<String>,
+    /// Creation window, as for `UsersUrlParams::created_after` and `created_before`.
+    pub created_after: Option<NaiveDateTime>,
+    pub created_before: Option<NaiveDateTime>,
+    /// Activity filter, as for `UsersUrlParams::is_active`.
+    pub is_active: Option<bool>,
+    /// Filters, as for `UsersUrlParams::filters`.
+    #[serde(flatten)]
+    pub filters: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsersGroupCountUrlParams {
+    pub by: String,
 }
 
 #[derive(Debug, Deserialize)]
+pub struct UsersDistinctUrlParams {
+    pub field: String,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct UsersRequestBody {
     pub id: Option<String>,
+    #[serde(default, deserialize_with = "crate::types::normalize::optional_email")]
     pub email: Option<String>,
     pub password: Option<String>,
+    #[serde(default, deserialize_with = "crate::types::normalize::optional_trimmed")]
     pub first_name: Option<String>,
+    #[serde(default, deserialize_with = "crate::types::normalize::optional_trimmed")]
     pub last_name: Option<String>,
+    /// Must match the caller's tenant if given, as for `UsersUrlParams::tenant_id`.
     pub tenant_id: Option<String>,
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsersSetActiveRequest {
+    pub is_active: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -37,8 +82,12 @@ pub enum UsersResponseType {
     PaginatedUsers {
         users: Vec<UserResponse>,
         total_count: u64,
+        /// `ceil(total_count / page_size)`; 0 when nothing matches.
+        total_pages: u64,
         page: u32,
         page_size: u32,
+        has_next: bool,
+        has_prev: bool,
     },
 }
 
@@ -48,7 +97,52 @@ pub struct UserResponse {
     pub email: String,
     pub first_name: String,
     pub last_name: String,
+    /// `first_name` and `last_name` joined by a space, skipping whichever is blank.
+    #[serde(default)]
+    pub display_name: String,
     pub tenant_id: String,
+    pub version: i32,
+    pub is_active: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
-} 
\ No newline at end of file
+} 
+
+/// Outcome of one entry in a bulk user creation: the new user's `id`, or why it failed.
+#[derive(Debug, Serialize)]
+pub struct UsersBulkResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsersBulkResponse {
+    pub created: bool,
+    pub results: Vec<UsersBulkResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DistinctValueCount {
+    pub value: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsersDistinctResponse {
+    pub field: String,
+    pub values: Vec<DistinctValueCount>,
+    pub total_count: u64,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+/// Joins first and last name for display, trimming each and skipping blank parts.
+pub fn display_name(first_name: &str, last_name: &str) -> String {
+    [first_name.trim(), last_name.trim()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
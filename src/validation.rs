@@ -0,0 +1,120 @@
+use crate::error::{ApiError, FieldError};
+
+/// Collects every invalid field of a request, so they are reported together in one `422`
+/// instead of one at a time.
+#[derive(Debug, Default)]
+pub struct FieldErrors(Vec<FieldError>);
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0.push(FieldError::new(field, message));
+    }
+
+    /// Returns `value`, recording `message` against `field` if it is missing or blank.
+    pub fn require(&mut self, field: &str, value: Option<&str>, message: &str) -> String {
+        match value.map(str::trim).filter(|value| !value.is_empty()) {
+            Some(value) => value.to_string(),
+            None => {
+                self.add(field, message);
+                String::new()
+            }
+        }
+    }
+
+    /// `Ok` if nothing was recorded, otherwise a `422` listing every field.
+    pub fn into_result(self) -> Result<(), ApiError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self.0))
+        }
+    }
+}
+
+/// Performs a basic structural check on an email address.
+///
+/// This is not full RFC 5322 validation; it rejects obviously malformed input (missing or
+/// repeated `@`, empty local part or domain, no dot in the domain, whitespace) before it
+/// reaches the database.
+pub fn validate_email(email: &str) -> Result<(), ApiError> {
+    let invalid = |reason: &str| Err(ApiError::BadRequest(format!("Invalid email address: {}", reason)));
+
+    if email.chars().any(char::is_whitespace) {
+        return invalid("must not contain whitespace");
+    }
+
+    let Some((local, domain)) = email.split_once('@') else {
+        return invalid("missing '@'");
+    };
+
+    if domain.contains('@') {
+        return invalid("must contain a single '@'");
+    }
+
+    if local.is_empty() {
+        return invalid("local part is empty");
+    }
+
+    if domain.is_empty() {
+        return invalid("domain is empty");
+    }
+
+    if !domain.contains('.') || domain.starts_with('.') || domain.ends_with('.') || domain.contains("..") {
+        return invalid("domain must be of the form example.com");
+    }
+
+    Ok(())
+}
+
+/// Minimum requirements a password must meet before it is hashed and stored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_digit: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_digit: true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum PasswordPolicyError {
+    #[error("Password must be at least {0} characters long")]
+    TooShort(usize),
+    #[error("Password must contain at least one letter")]
+    MissingLetter,
+    #[error("Password must contain at least one digit")]
+    MissingDigit,
+}
+
+impl From<PasswordPolicyError> for ApiError {
+    fn from(err: PasswordPolicyError) -> Self {
+        ApiError::BadRequest(err.to_string())
+    }
+}
+
+/// Checks `password` against `policy`. Length is counted in characters, not bytes.
+pub fn validate_password_strength(password: &str, policy: &PasswordPolicy) -> Result<(), PasswordPolicyError> {
+    if password.chars().count() < policy.min_length {
+        return Err(PasswordPolicyError::TooShort(policy.min_length));
+    }
+
+    if !password.chars().any(char::is_alphabetic) {
+        return Err(PasswordPolicyError::MissingLetter);
+    }
+
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        return Err(PasswordPolicyError::MissingDigit);
+    }
+
+    Ok(())
+}
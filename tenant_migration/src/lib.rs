@@ -9,10 +9,16 @@ impl MigratorTrait for TenantMigrator {
             Box::new(m20240101_000001_create_users_table::Migration),
             Box::new(m20240101_000002_create_products_table::Migration),
             Box::new(m20240101_000003_create_orders_table::Migration),
+            Box::new(m20240101_000004_add_version_to_users_table::Migration),
+            Box::new(m20240101_000005_add_sku_to_products_table::Migration),
+            Box::new(m20240101_000006_add_is_active_to_users_table::Migration),
         ]
     }
 }
 
 pub mod m20240101_000001_create_users_table;
 pub mod m20240101_000002_create_products_table;
-pub mod m20240101_000003_create_orders_table; 
\ No newline at end of file
+pub mod m20240101_000003_create_orders_table;
+pub mod m20240101_000004_add_version_to_users_table;
+pub mod m20240101_000005_add_sku_to_products_table;
+pub mod m20240101_000006_add_is_active_to_users_table; 
\ No newline at end of file
@@ -0,0 +1,56 @@
+use sea_orm_migration::prelude::*;
+
+/// Adds an optional `sku` to products. Each tenant has its own products table, so the unique
+/// index makes SKUs unique per tenant whichever `PRODUCT_UNIQUE_KEY` is configured; products
+/// without a SKU are unaffected.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+pub const SKU_INDEX: &str = "idx_products_sku";
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Products::Sku).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name(SKU_INDEX)
+                    .table(Products::Table)
+                    .col(Products::Sku)
+                    .unique()
+                    .if_not_exists()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name(SKU_INDEX).table(Products::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Products::Table)
+                    .drop_column(Products::Sku)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Products {
+    Table,
+    Sku,
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use chrono::Utc;
+use sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
+use uuid::Uuid;
+use rust_multi_tenant::{
+    MasterService, audit,
+    multi_tenancy::audit_retention_cutoff,
+    types::shared::AuditEntry,
+};
+use common::TestApp;
+
+/// Records one audit entry per target for `tenant_id`, stamped with the current time.
+async fn record(master_service: &MasterService, tenant_id: &str, targets: &[&str]) {
+    for target_id in targets {
+        master_service
+            .record_audit(AuditEntry {
+                tenant_id: tenant_id.to_string(),
+                actor_user_id: None,
+                action: audit::USER_UPDATED,
+                target_id: target_id.to_string(),
+                metadata: None,
+            })
+            .await
+            .unwrap();
+    }
+}
+
+/// Moves the tenant's entries for targets starting with `old-` `days` into the past.
+async fn backdate_old_entries(app: &TestApp, tenant_id: &str, days: i64) {
+    let created_at = Utc::now().naive_utc() - chrono::Duration::days(days);
+    let stmt = Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "UPDATE audit_log SET created_at = $1 WHERE tenant_id = $2 AND target_id LIKE 'old-%'",
+        vec![created_at.into(), tenant_id.into()],
+    );
+    app.state.tenant_manager.get_master_connection().await.execute(stmt).await.unwrap();
+}
+
+async fn remaining_targets(master_service: &MasterService, tenant_id: &str) -> Vec<String> {
+    let page = master_service.list_audit(tenant_id, None, 1, 100).await.unwrap();
+    let mut targets: Vec<String> = page.items.into_iter().map(|entry| entry.target_id).collect();
+    targets.sort();
+    targets
+}
+
+#[tokio::test]
+async fn purge_removes_only_entries_past_retention() {
+    let Some(app) = TestApp::new().await else { return };
+    let master_service = app.master_service().await;
+    let tenant_id = format!("audit_{}", Uuid::new_v4().simple());
+    let other_tenant_id = format!("audit_{}", Uuid::new_v4().simple());
+
+    record(&master_service, &tenant_id, &["old-1", "old-2", "old-3", "old-4", "old-5", "new-1", "new-2"]).await;
+    record(&master_service, &other_tenant_id, &["old-1"]).await;
+    backdate_old_entries(&app, &tenant_id, 40).await;
+    backdate_old_entries(&app, &other_tenant_id, 40).await;
+
+    // A batch size of 2 takes three batches, the last one short, to get through five entries
+    let older_than = audit_retention_cutoff(Utc::now().naive_utc(), 30);
+    let purged = master_service.purge_audit_log(&tenant_id, older_than, 2).await.unwrap();
+    assert_eq!(purged, 5);
+    assert_eq!(remaining_targets(&master_service, &tenant_id).await, vec!["new-1", "new-2"]);
+
+    // Other tenants are purged separately
+    assert_eq!(remaining_targets(&master_service, &other_tenant_id).await, vec!["old-1"]);
+
+    // Nothing is left to purge on a second run
+    assert_eq!(master_service.purge_audit_log(&tenant_id, older_than, 2).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn purge_stops_after_an_exactly_full_batch() {
+    let Some(app) = TestApp::new().await else { return };
+    let master_service = app.master_service().await;
+    let tenant_id = format!("audit_{}", Uuid::new_v4().simple());
+
+    record(&master_service, &tenant_id, &["old-1", "old-2", "old-3", "old-4", "new-1"]).await;
+    backdate_old_entries(&app, &tenant_id, 40).await;
+
+    let older_than = audit_retention_cutoff(Utc::now().naive_utc(), 30);
+    assert_eq!(master_service.purge_audit_log(&tenant_id, older_than, 2).await.unwrap(), 4);
+    assert_eq!(remaining_targets(&master_service, &tenant_id).await, vec!["new-1"]);
+}
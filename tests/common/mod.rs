@@ -0,0 +1,164 @@
+//! Setup shared by the database-backed integration tests.
+//!
+//! These tests need a Postgres server they may create databases on. `TEST_DATABASE_URL`
+//! names a disposable database on it, which the tests use as the master database; when it is
+//! unset, every test returns early without checking anything.
+
+#![allow(dead_code)]
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use axum::body::{Body, to_bytes};
+use axum::response::Response;
+use chrono::Utc;
+use reqwest::Url;
+use serde_json::Value;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+use rust_multi_tenant::{
+    CreateTenantRequest, MasterService, TenantConnectionManager, TenantContext,
+    database::apply_master_migrations,
+    middlewares::{MetricsHandle, RateLimiter, RevokedTokens, install_metrics_recorder},
+    types::config::{AppConfig, MigrationMode},
+    types::shared::AppState,
+};
+
+static TEST_DATABASE_URL: OnceLock<Option<String>> = OnceLock::new();
+static METRICS: OnceLock<MetricsHandle> = OnceLock::new();
+static MASTER_MIGRATED: Mutex<bool> = Mutex::const_new(false);
+
+/// Application state wired to the test database, as `main` wires it to the real one.
+pub struct TestApp {
+    pub state: AppState,
+}
+
+impl TestApp {
+    /// `None` when `TEST_DATABASE_URL` is unset.
+    pub async fn new() -> Option<Self> {
+        Self::with_config(|_| {}).await
+    }
+
+    /// Like `new`, with `customize` applied to the configuration read from the environment.
+    pub async fn with_config(customize: impl FnOnce(&mut AppConfig)) -> Option<Self> {
+        let url = TEST_DATABASE_URL.get_or_init(|| {
+            let url = std::env::var("TEST_DATABASE_URL").ok();
+            if let Some(url) = &url {
+                set_test_env(url);
+            }
+            url
+        });
+        if url.is_none() {
+            eprintln!("TEST_DATABASE_URL is not set; skipping database test");
+            return None;
+        }
+
+        let mut config = AppConfig::from_env().expect("test configuration");
+        customize(&mut config);
+
+        let tenant_manager = TenantConnectionManager::new(config.database_config.clone())
+            .await
+            .expect("connect to TEST_DATABASE_URL")
+            .with_product_unique_key(config.product_unique_key);
+
+        // Tests run concurrently, but the master schema only has to be created once
+        {
+            let mut migrated = MASTER_MIGRATED.lock().await;
+            if !*migrated {
+                let master_db = tenant_manager.get_master_connection().await;
+                apply_master_migrations(&master_db, MigrationMode::Auto).await.expect("master migrations");
+                MasterService::new(master_db).seed_default_permissions().await.expect("seed permissions");
+                *migrated = true;
+            }
+        }
+
+        let metrics = METRICS
+            .get_or_init(|| install_metrics_recorder().expect("metrics recorder"))
+            .clone();
+
+        let state = AppState {
+            tenant_manager,
+            password_policy: config.password_policy(),
+            max_token_permissions: config.max_token_permissions,
+            product_unique_key: config.product_unique_key,
+            config: Arc::new(config.clone()),
+            jwt_keys: config.jwt_keys().expect("JWT keys"),
+            jwt_expiration: config.jwt_expiration,
+            rate_limiter: RateLimiter::new(20, Duration::from_secs(60)),
+            revoked_tokens: RevokedTokens::new(),
+            metrics,
+        };
+
+        Some(Self { state })
+    }
+
+    pub async fn master_service(&self) -> MasterService {
+        MasterService::new(self.state.tenant_manager.get_master_connection().await)
+    }
+
+    /// Creates an active tenant with a fresh id and a migrated database, returning the id.
+    pub async fn create_tenant(&self) -> String {
+        let tenant_id = format!("test_{}", &Uuid::new_v4().simple().to_string()[..12]);
+        let request = CreateTenantRequest {
+            id: Some(tenant_id.clone()),
+            name: format!("Test tenant {}", tenant_id),
+            region: None,
+        };
+
+        self.master_service().await.create_tenant(tenant_id.clone(), request).await.expect("tenant row");
+        self.state
+            .tenant_manager
+            .create_tenant_database(&tenant_id, None)
+            .await
+            .expect("tenant database");
+        tenant_id
+    }
+
+    /// Drops a tenant made by `create_tenant`, database and master row alike.
+    pub async fn drop_tenant(&self, tenant_id: &str) {
+        self.state.tenant_manager.invalidate_tenant(tenant_id).await;
+        self.state
+            .tenant_manager
+            .drop_tenant_database(tenant_id, None)
+            .await
+            .expect("drop tenant database");
+        self.master_service().await.discard_tenant(tenant_id).await.expect("drop tenant row");
+    }
+
+    /// The context of a caller in `tenant_id` holding `permissions`, as `auth_middleware`
+    /// would extract it from their token.
+    pub fn context(&self, tenant_id: &str, permissions: &[&str]) -> TenantContext {
+        TenantContext {
+            tenant_id: tenant_id.to_string(),
+            user_id: Uuid::new_v4().to_string(),
+            permissions: permissions.iter().map(|permission| permission.to_string()).collect(),
+            token_id: Uuid::new_v4().to_string(),
+            token_expires_at: Utc::now().naive_utc() + chrono::Duration::hours(1),
+        }
+    }
+}
+
+/// The JSON body of a handler's response.
+pub async fn json_body(response: Response<Body>) -> Value {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.expect("response body");
+    serde_json::from_slice(&bytes).expect("JSON response body")
+}
+
+/// Points the configuration variables `AppConfig::from_env` reads at the test database.
+fn set_test_env(url: &str) {
+    let parsed = Url::parse(url).expect("TEST_DATABASE_URL must be a URL");
+    let vars = [
+        ("MASTER_DATABASE_URL", url.to_string()),
+        ("DB_USERNAME", parsed.username().to_string()),
+        ("DB_PASSWORD", parsed.password().unwrap_or_default().to_string()),
+        ("DB_HOST", parsed.host_str().unwrap_or("localhost").to_string()),
+        ("DB_PORT", parsed.port().unwrap_or(5432).to_string()),
+        ("DB_MAX_CONNECTIONS", "5".to_string()),
+        ("JWT_SECRET", "integration-test-secret".to_string()),
+    ];
+
+    for (key, value) in vars {
+        // SAFETY: runs once, inside the `TEST_DATABASE_URL` initializer, which every test
+        // goes through before it touches the environment
+        unsafe { std::env::set_var(key, value) };
+    }
+}
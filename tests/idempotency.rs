@@ -0,0 +1,99 @@
+mod common;
+
+use axum::{extract::State, http::{HeaderMap, HeaderValue, StatusCode}, response::IntoResponse};
+use chrono::Utc;
+use uuid::Uuid;
+use rust_multi_tenant::{
+    ApiError, TenantService, UsersRequestBody,
+    controllers::users_create,
+    extract::Json,
+    idempotency::IDEMPOTENCY_KEY_HEADER,
+    permissions::USERS_WRITE,
+};
+use common::{TestApp, json_body};
+
+fn new_user(email: &str) -> UsersRequestBody {
+    UsersRequestBody {
+        id: None,
+        email: Some(email.to_string()),
+        password: None,
+        first_name: Some("Jane".to_string()),
+        last_name: Some("Doe".to_string()),
+        tenant_id: None,
+        version: None,
+    }
+}
+
+fn with_key(key: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_str(key).unwrap());
+    headers
+}
+
+async fn create(app: &TestApp, tenant_id: &str, headers: HeaderMap, body: UsersRequestBody) -> Result<axum::response::Response, ApiError> {
+    users_create(State(app.state.clone()), app.context(tenant_id, &[USERS_WRITE]), headers, Json(body)).await
+}
+
+async fn user_count(app: &TestApp, tenant_id: &str) -> usize {
+    let tenant_db = app.state.tenant_manager.get_tenant_connection(tenant_id).await.unwrap();
+    TenantService::new(tenant_db).get_users().await.unwrap().len()
+}
+
+#[tokio::test]
+async fn repeated_key_replays_the_stored_response() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+
+    let first = create(&app, &tenant_id, with_key("create-jane"), new_user("jane@example.com")).await.unwrap();
+    assert_eq!(first.status(), StatusCode::CREATED);
+    let first = json_body(first).await;
+
+    let retry = create(&app, &tenant_id, with_key("create-jane"), new_user("jane@example.com")).await.unwrap();
+    assert_eq!(retry.status(), StatusCode::CREATED);
+    assert_eq!(json_body(retry).await, first);
+    assert_eq!(user_count(&app, &tenant_id).await, 1);
+
+    // Without the key the same body is a new request, and runs into the unique email
+    let result = create(&app, &tenant_id, HeaderMap::new(), new_user("jane@example.com")).await;
+    assert_eq!(result.expect_err("duplicate email accepted").into_response().status(), StatusCode::CONFLICT);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn key_reused_for_a_different_body_is_rejected() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+
+    create(&app, &tenant_id, with_key("create-user"), new_user("jane@example.com")).await.unwrap();
+
+    let result = create(&app, &tenant_id, with_key("create-user"), new_user("john@example.com")).await;
+    let error = result.expect_err("different body replayed");
+    assert!(matches!(error, ApiError::UnprocessableEntity(_)), "unexpected error {:?}", error);
+    assert_eq!(user_count(&app, &tenant_id).await, 1);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn unfinished_claim_is_taken_over_only_once_abandoned() {
+    let Some(app) = TestApp::new().await else { return };
+    let master_service = app.master_service().await;
+    let scope = format!("idem_{}", Uuid::new_v4().simple());
+    let now = Utc::now().naive_utc();
+    let long_ago = now - chrono::Duration::hours(1);
+
+    assert!(master_service.claim_idempotency_key(&scope, "key", "hash-1", long_ago).await.unwrap());
+    // Still fresh, so a second request must wait for it
+    assert!(!master_service.claim_idempotency_key(&scope, "key", "hash-1", long_ago).await.unwrap());
+
+    // Once every claim older than now counts as abandoned, the key can be taken over
+    let later = now + chrono::Duration::seconds(1);
+    assert!(master_service.claim_idempotency_key(&scope, "key", "hash-2", later).await.unwrap());
+    assert_eq!(master_service.idempotency_record(&scope, "key").await.unwrap().unwrap().request_hash, "hash-2");
+
+    // A completed key is never taken over
+    master_service.complete_idempotency_key(&scope, "key", 201, &serde_json::json!({})).await.unwrap();
+    let much_later = now + chrono::Duration::hours(1);
+    assert!(!master_service.claim_idempotency_key(&scope, "key", "hash-3", much_later).await.unwrap());
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use axum::{extract::{Query, State}, http::StatusCode, response::IntoResponse};
+use sea_orm::{ActiveModelTrait, Set, prelude::Decimal};
+use uuid::Uuid;
+use rust_multi_tenant::{
+    ApiError,
+    controllers::{products_create, products_index},
+    entities::tenant::products::ActiveModel,
+    error::unique_violation_constraint,
+    extract::Json,
+    multi_tenancy::PRODUCT_NAME_INDEX,
+    types::config::ProductUniqueKey,
+    types::products::{ProductsRequestBody, ProductsUrlParams},
+};
+use common::{TestApp, json_body};
+
+fn product(name: &str, sku: Option<&str>) -> ProductsRequestBody {
+    ProductsRequestBody {
+        id: None,
+        name: Some(name.to_string()),
+        sku: sku.map(str::to_string),
+        description: None,
+        price: Some(Decimal::new(1999, 2)),
+    }
+}
+
+async fn create(app: &TestApp, tenant_id: &str, body: ProductsRequestBody) -> Result<StatusCode, ApiError> {
+    let context = app.context(tenant_id, &[]);
+    products_create(State(app.state.clone()), context, Json(body))
+        .await
+        .map(|response| response.into_response().status())
+}
+
+/// Lists the tenant's products as `products_index` would for a request with `page`.
+async fn list(app: &TestApp, tenant_id: &str, page: Option<u32>) -> serde_json::Value {
+    let params = ProductsUrlParams {
+        id: None,
+        page,
+        page_size: Some(2),
+        name: None,
+        min_price: None,
+        max_price: None,
+        sort_by: None,
+    };
+    let response = products_index(Query(params), State(app.state.clone()), app.context(tenant_id, &[]))
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    json_body(response).await
+}
+
+async fn conflict_message(result: Result<StatusCode, ApiError>) -> String {
+    let response = result.expect_err("duplicate accepted").into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    json_body(response).await["error"]["message"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn created_product_is_returned_and_listed() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+
+    let response = products_create(State(app.state.clone()), app.context(&tenant_id, &[]), Json(product("Widget", Some("W-1"))))
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let created = json_body(response).await;
+    assert_eq!(created["name"], "Widget");
+    assert_eq!(created["sku"], "W-1");
+
+    let listed = list(&app, &tenant_id, None).await;
+    let products = listed["MultipleProducts"].as_array().unwrap();
+    assert_eq!(products.len(), 1);
+    assert_eq!(products[0]["id"], created["id"]);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn unpaginated_listing_stops_at_the_row_cap() {
+    let Some(app) = TestApp::with_config(|config| config.max_unpaginated_rows = 2).await else {
+        return;
+    };
+    let tenant_id = app.create_tenant().await;
+
+    for name in ["Widget", "Gadget", "Gizmo"] {
+        create(&app, &tenant_id, product(name, None)).await.unwrap();
+    }
+
+    let listed = list(&app, &tenant_id, None).await;
+    assert_eq!(listed["MultipleProducts"].as_array().unwrap().len(), 2);
+
+    // Paging still reaches every product
+    let paginated = list(&app, &tenant_id, Some(2)).await;
+    assert_eq!(paginated["PaginatedProducts"]["total_count"], 3);
+    assert_eq!(paginated["PaginatedProducts"]["products"].as_array().unwrap().len(), 1);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn duplicate_sku_is_a_conflict() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+
+    assert_eq!(create(&app, &tenant_id, product("Widget", Some("W-1"))).await.unwrap(), StatusCode::CREATED);
+    // Products without a SKU never clash on it
+    assert_eq!(create(&app, &tenant_id, product("Gadget", None)).await.unwrap(), StatusCode::CREATED);
+    assert_eq!(create(&app, &tenant_id, product("Gizmo", None)).await.unwrap(), StatusCode::CREATED);
+
+    let message = conflict_message(create(&app, &tenant_id, product("Widget v2", Some("W-1"))).await).await;
+    assert!(message.contains("SKU"), "unexpected message {:?}", message);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn name_mode_rejects_duplicate_names_and_skus() {
+    let Some(app) = TestApp::with_config(|config| config.product_unique_key = ProductUniqueKey::Name).await else {
+        return;
+    };
+    let tenant_id = app.create_tenant().await;
+
+    assert_eq!(create(&app, &tenant_id, product("Widget", Some("W-1"))).await.unwrap(), StatusCode::CREATED);
+
+    let message = conflict_message(create(&app, &tenant_id, product("Widget", Some("W-2"))).await).await;
+    assert!(message.contains("name"), "unexpected message {:?}", message);
+
+    let message = conflict_message(create(&app, &tenant_id, product("Gadget", Some("W-1"))).await).await;
+    assert!(message.contains("SKU"), "unexpected message {:?}", message);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn name_mode_backs_names_with_a_unique_index() {
+    let Some(app) = TestApp::with_config(|config| config.product_unique_key = ProductUniqueKey::Name).await else {
+        return;
+    };
+    let tenant_id = app.create_tenant().await;
+    let tenant_db = app.state.tenant_manager.get_tenant_connection(&tenant_id).await.unwrap();
+
+    // Inserting directly skips the handler's check, as a concurrent create would
+    let insert = |name: &str| ActiveModel {
+        id: Set(Uuid::new_v4().to_string()),
+        name: Set(name.to_string()),
+        price: Set(Decimal::new(500, 2)),
+        ..Default::default()
+    };
+    insert("Widget").insert(&tenant_db).await.unwrap();
+    let error = insert("Widget").insert(&tenant_db).await.expect_err("duplicate name inserted");
+    assert_eq!(unique_violation_constraint(&error), Some(PRODUCT_NAME_INDEX));
+
+    app.drop_tenant(&tenant_id).await;
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use common::TestApp;
+
+#[tokio::test]
+async fn eviction_hook_receives_the_evicted_tenant() {
+    let Some(app) = TestApp::with_config(|config| config.database_config.max_cached_tenants = 1).await else {
+        return;
+    };
+    let first = app.create_tenant().await;
+    let second = app.create_tenant().await;
+
+    let evicted = Arc::new(Mutex::new(Vec::new()));
+    let tenant_manager = app.state.tenant_manager.clone().with_eviction_hook({
+        let evicted = evicted.clone();
+        move |tenant_id| evicted.lock().unwrap().push(tenant_id.to_string())
+    });
+
+    tenant_manager.get_tenant_connection(&first).await.unwrap();
+    assert!(evicted.lock().unwrap().is_empty());
+
+    // The cache holds one tenant, so connecting the second pushes the first out
+    tenant_manager.get_tenant_connection(&second).await.unwrap();
+    assert_eq!(*evicted.lock().unwrap(), vec![first.clone()]);
+
+    app.drop_tenant(&first).await;
+    app.drop_tenant(&second).await;
+}
+
+#[tokio::test]
+async fn evicted_and_invalidated_pools_are_counted_as_closed() {
+    let Some(app) = TestApp::with_config(|config| config.database_config.max_cached_tenants = 1).await else {
+        return;
+    };
+    let first = app.create_tenant().await;
+    let second = app.create_tenant().await;
+    let tenant_manager = &app.state.tenant_manager;
+    assert_eq!(tenant_manager.closed_connection_count(), 0);
+
+    tenant_manager.get_tenant_connection(&first).await.unwrap();
+    tenant_manager.get_tenant_connection(&second).await.unwrap();
+    assert_eq!(tenant_manager.closed_connection_count(), 1);
+
+    tenant_manager.invalidate_tenant(&second).await;
+    assert_eq!(tenant_manager.closed_connection_count(), 2);
+
+    // Nothing is cached for the first tenant any more, so there is nothing to close
+    tenant_manager.invalidate_tenant(&first).await;
+    assert_eq!(tenant_manager.closed_connection_count(), 2);
+
+    app.drop_tenant(&first).await;
+    app.drop_tenant(&second).await;
+}
+
+#[tokio::test]
+async fn invalidating_a_suspended_tenant_cuts_off_its_connection() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+    let tenant_manager = &app.state.tenant_manager;
+
+    tenant_manager.get_tenant_connection(&tenant_id).await.unwrap();
+    app.master_service().await.set_tenant_status(&tenant_id, "suspended").await.unwrap();
+    tenant_manager.invalidate_tenant(&tenant_id).await;
+
+    assert!(tenant_manager.get_tenant_connection(&tenant_id).await.is_err());
+
+    app.master_service().await.set_tenant_status(&tenant_id, "active").await.unwrap();
+    assert!(tenant_manager.get_tenant_connection(&tenant_id).await.is_ok());
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn cached_connection_is_rechecked_once_the_window_passes() {
+    let Some(app) = TestApp::with_config(|config| config.database_config.tenant_status_recheck_secs = 0).await else {
+        return;
+    };
+    let tenant_id = app.create_tenant().await;
+    let tenant_manager = &app.state.tenant_manager;
+
+    tenant_manager.get_tenant_connection(&tenant_id).await.unwrap();
+    // Suspended without invalidating, as another instance would see it
+    app.master_service().await.set_tenant_status(&tenant_id, "suspended").await.unwrap();
+
+    assert!(tenant_manager.get_tenant_connection(&tenant_id).await.is_err());
+    assert_eq!(tenant_manager.stats().await.cached_connections, 0);
+
+    app.master_service().await.set_tenant_status(&tenant_id, "active").await.unwrap();
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn warmup_caches_connections_up_to_the_cap() {
+    let Some(app) = TestApp::with_config(|config| config.database_config.max_cached_tenants = 2).await else {
+        return;
+    };
+    let tenants = vec![app.create_tenant().await, app.create_tenant().await, app.create_tenant().await];
+    let tenant_manager = &app.state.tenant_manager;
+    assert_eq!(tenant_manager.stats().await.cached_connections, 0);
+
+    // Unknown tenants are skipped rather than failing the warm-up
+    let mut tenant_ids = vec!["test_missing".to_string()];
+    tenant_ids.extend(tenants.iter().cloned());
+    assert_eq!(tenant_manager.warmup(&tenant_ids).await, 2);
+
+    // The third tenant is left cold instead of evicting one of the first two
+    assert_eq!(tenant_manager.stats().await.cached_connections, 2);
+    assert_eq!(tenant_manager.closed_connection_count(), 0);
+
+    for tenant_id in &tenants {
+        app.drop_tenant(tenant_id).await;
+    }
+}
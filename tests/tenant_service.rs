@@ -0,0 +1,66 @@
+mod common;
+
+use rust_multi_tenant::{CreateUserRequest, TenantService};
+use common::TestApp;
+
+/// Adds `count` users named `user-0`, `user-1`, ... to the tenant, in that order.
+async fn add_users(service: &TenantService, count: usize) {
+    for n in 0..count {
+        service
+            .create_user(CreateUserRequest {
+                email: format!("user-{}@example.com", n),
+                password: String::new(),
+                first_name: format!("user-{}", n),
+                last_name: "Test".to_string(),
+                permissions: None,
+            })
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn get_users_stops_at_the_row_cap() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+    let tenant_db = app.state.tenant_manager.get_tenant_connection(&tenant_id).await.unwrap();
+
+    add_users(&TenantService::new(tenant_db.clone()), 5).await;
+
+    let capped = TenantService::new(tenant_db.clone()).with_max_unpaginated_rows(3);
+    let users = capped.get_users().await.unwrap();
+    assert_eq!(users.len(), 3);
+    assert_eq!(users[0].first_name, "user-0");
+
+    // A cap the tenant fits under returns everyone
+    let roomy = TenantService::new(tenant_db).with_max_unpaginated_rows(5);
+    assert_eq!(roomy.get_users().await.unwrap().len(), 5);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn get_users_paginated_splits_users_across_pages() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+    let service = TenantService::new(app.state.tenant_manager.get_tenant_connection(&tenant_id).await.unwrap());
+
+    add_users(&service, 5).await;
+
+    let mut seen = Vec::new();
+    for (page, expected) in [(1, 2), (2, 2), (3, 1), (4, 0)] {
+        let (users, total_count) = service.get_users_paginated(page, 2).await.unwrap();
+        assert_eq!(users.len(), expected, "page {}", page);
+        assert_eq!(total_count, 5);
+        seen.extend(users.into_iter().map(|user| user.first_name));
+    }
+    // Every user shows up exactly once, in creation order
+    assert_eq!(seen, (0..5).map(|n| format!("user-{}", n)).collect::<Vec<_>>());
+
+    // Page 0 is read as page 1, and a page size of 0 as 1
+    let (first, _) = service.get_users_paginated(0, 0).await.unwrap();
+    assert_eq!(first.len(), 1);
+    assert_eq!(first[0].first_name, "user-0");
+
+    app.drop_tenant(&tenant_id).await;
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use std::collections::BTreeMap;
+use axum::{extract::{Query, State}, http::{HeaderMap, StatusCode}, response::IntoResponse};
+use serde_json::Value;
+use rust_multi_tenant::{
+    ApiError, UsersRequestBody,
+    extract::Json,
+    controllers::{users_create, users_index, users_update},
+    permissions::{USERS_READ, USERS_WRITE},
+    types::users::UsersUrlParams,
+};
+use common::{TestApp, json_body};
+
+fn new_user(email: &str) -> UsersRequestBody {
+    UsersRequestBody {
+        id: None,
+        email: Some(email.to_string()),
+        password: None,
+        first_name: Some("Jane".to_string()),
+        last_name: Some("Doe".to_string()),
+        tenant_id: None,
+        version: None,
+    }
+}
+
+fn rename(id: &str, first_name: &str, version: Option<i32>) -> UsersRequestBody {
+    UsersRequestBody {
+        id: Some(id.to_string()),
+        email: None,
+        password: None,
+        first_name: Some(first_name.to_string()),
+        last_name: None,
+        tenant_id: None,
+        version,
+    }
+}
+
+/// The ids `users_index` lists, sorted by `sort_by`, for `page` of two users or unpaginated.
+async fn listed_ids(app: &TestApp, tenant_id: &str, sort_by: Option<&str>, page: Option<u32>) -> Vec<Value> {
+    let params = UsersUrlParams {
+        id: None,
+        page,
+        page_size: Some(2),
+        tenant_id: None,
+        sort_by: sort_by.map(str::to_string),
+        sort_dir: None,
+        q: None,
+        created_after: None,
+        created_before: None,
+        is_active: None,
+        filters: BTreeMap::new(),
+    };
+    let response = users_index(Query(params), State(app.state.clone()), app.context(tenant_id, &[USERS_READ]))
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = json_body(response).await;
+    let users = match page {
+        Some(_) => &body["PaginatedUsers"]["users"],
+        None => &body["MultipleUsers"],
+    };
+    users.as_array().unwrap().iter().map(|user| user["id"].clone()).collect()
+}
+
+#[tokio::test]
+async fn repeated_listings_keep_the_same_order() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+    let context = app.context(&tenant_id, &[USERS_READ, USERS_WRITE]);
+
+    // Every user shares a last name, so sorting by it leaves only the tie-breaks to order them
+    for n in 0..5 {
+        users_create(State(app.state.clone()), context.clone(), HeaderMap::new(), Json(new_user(&format!("user-{}@example.com", n))))
+            .await
+            .unwrap();
+    }
+
+    for sort_by in [None, Some("last_name")] {
+        let listed = listed_ids(&app, &tenant_id, sort_by, None).await;
+        assert_eq!(listed.len(), 5);
+        assert_eq!(listed_ids(&app, &tenant_id, sort_by, None).await, listed, "sort_by {:?}", sort_by);
+
+        let mut paged = Vec::new();
+        for page in 1..=3 {
+            paged.extend(listed_ids(&app, &tenant_id, sort_by, Some(page)).await);
+        }
+        assert_eq!(paged, listed, "sort_by {:?}", sort_by);
+    }
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn update_with_the_current_version_bumps_it() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+    let context = app.context(&tenant_id, &[USERS_READ, USERS_WRITE]);
+
+    let response = users_create(State(app.state.clone()), context.clone(), HeaderMap::new(), Json(new_user("jane@example.com")))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let created = json_body(response).await;
+    let id = created["id"].as_str().unwrap();
+    let version = created["version"].as_i64().unwrap() as i32;
+
+    let response = users_update(State(app.state.clone()), context, Json(rename(id, "Janet", Some(version))))
+        .await
+        .unwrap()
+        .into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    let updated = json_body(response).await;
+    assert_eq!(updated["first_name"], "Janet");
+    assert_eq!(updated["version"].as_i64().unwrap() as i32, version + 1);
+
+    app.drop_tenant(&tenant_id).await;
+}
+
+#[tokio::test]
+async fn update_with_a_stale_version_is_a_conflict() {
+    let Some(app) = TestApp::new().await else { return };
+    let tenant_id = app.create_tenant().await;
+    let context = app.context(&tenant_id, &[USERS_READ, USERS_WRITE]);
+
+    let response = users_create(State(app.state.clone()), context.clone(), HeaderMap::new(), Json(new_user("jane@example.com")))
+        .await
+        .unwrap();
+    let created = json_body(response).await;
+    let id = created["id"].as_str().unwrap();
+    let version = created["version"].as_i64().unwrap() as i32;
+
+    // A first writer moves the user on to the next version
+    users_update(State(app.state.clone()), context.clone(), Json(rename(id, "Janet", Some(version))))
+        .await
+        .unwrap();
+
+    // A second writer still holding the original version is turned away
+    let result = users_update(State(app.state.clone()), context, Json(rename(id, "Jenny", Some(version)))).await;
+    let error = result.err().expect("stale version accepted");
+    assert!(matches!(error, ApiError::Conflict(_)), "unexpected error {:?}", error);
+    assert_eq!(error.into_response().status(), StatusCode::CONFLICT);
+
+    app.drop_tenant(&tenant_id).await;
+}